@@ -30,7 +30,7 @@ use {defmt_rtt as _, esp_backtrace as _};
 
 use m5dial_bsp::bsp::*;
 extern crate alloc;
-use m5dial_bsp::rtc8563::{Date, Time};
+use m5dial_bsp::rtc8563::{Date, DateTime, Time};
 
 esp_bootloader_esp_idf::esp_app_desc!();
 
@@ -52,21 +52,20 @@ struct Clock {
     date_style: SevenSegmentStyle<Rgb565>,
 }
 
-fn modify_wrap<T>(value: &mut T, increment: i8, ceiling: T)
+fn modify_wrap<T>(value: &mut T, increment: i8, min: T, max: T)
 where
-    T: Copy + PartialOrd + From<u8>,
+    T: Copy,
     i16: From<T>,
     T: TryFrom<i16>,
 {
     let new_val = i16::from(*value) + increment as i16;
-    let ceiling_i16 = i16::from(ceiling);
 
-    *value = if new_val >= ceiling_i16 {
-        T::from(0u8)
-    } else if new_val < 0 {
-        T::try_from(ceiling_i16 - 1).unwrap_or(T::from(0u8))
+    *value = if new_val > i16::from(max) {
+        min
+    } else if new_val < i16::from(min) {
+        max
     } else {
-        T::try_from(new_val).unwrap_or(T::from(0u8))
+        T::try_from(new_val).unwrap_or(min)
     };
 }
 
@@ -94,26 +93,20 @@ impl Clock {
         }
     }
 
-    fn update_from(&mut self, rtc_time: &Time) {
+    fn update_from(&mut self, rtc_datetime: &DateTime) {
         if self.mode == ClockMode::Run {
-            self.time = *rtc_time;
+            self.time = rtc_datetime.time;
+            self.date = rtc_datetime.date;
         }
     }
 
-    fn update_date_from(&mut self, rtc_date: &Date) {
-        if self.mode == ClockMode::Run {
-            self.date = *rtc_date;
+    fn get_datetime(&self) -> DateTime {
+        DateTime {
+            date: self.date,
+            time: self.time,
         }
     }
 
-    fn get_time(&self) -> &Time {
-        &self.time
-    }
-
-    fn get_date(&self) -> &Date {
-        &self.date
-    }
-
     fn on_click(&mut self) {
         self.mode = match self.mode {
             ClockMode::Run => ClockMode::EditHours,
@@ -129,21 +122,27 @@ impl Clock {
         match self.mode {
             ClockMode::Run => {}
             ClockMode::EditHours => {
-                modify_wrap(&mut self.time.hours, delta, 24);
+                modify_wrap(&mut self.time.hours, delta, 0, 23);
             }
             ClockMode::EditMinutes => {
-                modify_wrap(&mut self.time.minutes, delta, 60);
+                modify_wrap(&mut self.time.minutes, delta, 0, 59);
             }
             ClockMode::EditDay => {
-                modify_wrap(&mut self.date.day, delta, 31);
+                let last_day = self.date.days_in_month();
+                modify_wrap(&mut self.date.day, delta, 1, last_day);
             }
             ClockMode::EditMonth => {
-                modify_wrap(&mut self.date.month, delta, 12);
+                modify_wrap(&mut self.date.month, delta, 1, 12);
             }
             ClockMode::EditYear => {
-                modify_wrap(&mut self.date.year, delta, 3000);
+                modify_wrap(&mut self.date.year, delta, 1900, 2099);
             }
         }
+
+        // Keep the day within the month, e.g. after moving from January 31
+        // to February, and update the weekday.
+        self.date.day = self.date.day.min(self.date.days_in_month()).max(1);
+        self.date = self.date.with_weekday();
     }
 
     fn up(&mut self) {
@@ -164,12 +163,7 @@ impl Clock {
         let center_point = display.bounding_box().center() + Point::new(0, 20);
 
         // Render time string
-        write!(
-            &mut buffer,
-            "{:02}:{:02}:{:02}",
-            self.time.hours, self.time.minutes, self.time.seconds
-        )
-        .unwrap_or_else(|_| {
+        write!(&mut buffer, "{}", self.time).unwrap_or_else(|_| {
             error!("Error writing buffer!");
         });
 
@@ -189,12 +183,7 @@ impl Clock {
         // Render date
         let date_offset = Point::new(0, 2 * self.date_style.digit_size.height as i32);
         buffer.clear();
-        write!(
-            &mut buffer,
-            "{:02}-{:02}-{:04}",
-            self.date.day, self.date.month, self.date.year
-        )
-        .unwrap_or_else(|_| {
+        write!(&mut buffer, "{}", self.date).unwrap_or_else(|_| {
             error!("Error writing buffer!");
         });
 
@@ -235,19 +224,20 @@ impl Clock {
             let p1 = match self.mode {
                 ClockMode::EditHours => Point::new(20, y_offset),
                 ClockMode::EditMinutes => Point::new(center_point.x - 25, y_offset),
-                ClockMode::EditDay => Point::new(date_text.bounding_box().top_left.x, y_offset),
-                ClockMode::EditMonth => Point::new(
+                // Date is rendered as YYYY-MM-DD
+                ClockMode::EditDay => Point::new(
                     date_text.bounding_box().top_left.x
-                        + 3 * (self.date_style.digit_spacing + self.date_style.digit_size.width)
+                        + 8 * (self.date_style.digit_spacing + self.date_style.digit_size.width)
                             as i32,
                     y_offset,
                 ),
-                ClockMode::EditYear => Point::new(
+                ClockMode::EditMonth => Point::new(
                     date_text.bounding_box().top_left.x
-                        + 6 * (self.date_style.digit_spacing + self.date_style.digit_size.width)
+                        + 5 * (self.date_style.digit_spacing + self.date_style.digit_size.width)
                             as i32,
                     y_offset,
                 ),
+                ClockMode::EditYear => Point::new(date_text.bounding_box().top_left.x, y_offset),
                 _ => Point::new(0, 0),
             };
             let p2 = p1 + Point::new(line_length, 0);
@@ -282,6 +272,9 @@ fn main() -> ! {
 
     let mut clock = Clock::new(40, 16);
     info!("Clock demo running!");
+    if let Ok(now) = rtc.get_datetime(&mut tp_i2c) {
+        info!("RTC date & time: {}", now);
+    }
 
     loop {
         // Map encoder action to clock
@@ -298,18 +291,13 @@ fn main() -> ! {
         }
 
         if clock.is_running() {
-            // Get the RTC time and update the Clock APP
-            if let Ok(now) = rtc.get_time(&mut tp_i2c) {
+            // Get the RTC date & time and update the Clock APP
+            if let Ok(now) = rtc.get_datetime(&mut tp_i2c) {
                 clock.update_from(&now);
             }
-
-            if let Ok(today) = rtc.get_date(&mut tp_i2c) {
-                clock.update_date_from(&today);
-            }
         } else {
             // Get the on-screen time & Date and set the RTC
-            let _ = rtc.set_time(&mut tp_i2c, clock.get_time());
-            let _ = rtc.set_date(&mut tp_i2c, clock.get_date());
+            let _ = rtc.set_datetime(&mut tp_i2c, &clock.get_datetime());
         }
 
         // Update display
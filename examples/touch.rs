@@ -1,11 +1,12 @@
 #![no_std]
 #![no_main]
 ///! This example draws a cycle on the screen that you can move with your finger.
-///! The cycle can change color on clicks. This demonstrate the Touch driver
-///! functionality
+///! The cycle can change color on clicks or double taps. This demonstrate the
+///! Touch driver functionality
 // ESP32 Hardware abstraction
 use esp_hal::clock::CpuClock;
 use esp_hal::main;
+use esp_hal::time::Instant;
 
 // Embedded graphics
 use embedded_graphics::{
@@ -59,6 +60,8 @@ fn main() -> ! {
     let (w, h) = display.bounds();
     let mut point = Point::new((w / 2).into(), (h / 2).into());
 
+    let mut gestures = GestureRecognizer::default();
+
     let mut need_redraw = true;
     loop {
         // Change Test color on button push
@@ -69,17 +72,19 @@ fn main() -> ! {
             }
         }
 
-        if let Ok(x) = touch.count(&mut tp_i2c) {
-            if let Some(_) = x {
-                //info!("Touch: {}", touch_count);
-                if let Ok(p) = touch.get_point(&mut tp_i2c, 0) {
-                    info!("Pos: x={} y={}", p.x, p.y);
-                    point.x = p.x.into();
-                    point.y = p.y.into();
-                    need_redraw = true;
-                } else {
-                    error!("I2C error");
-                }
+        if let Ok(touches) = touch.read_touches(&mut tp_i2c) {
+            if let Some(p) = touches.get(0) {
+                info!("Pos: x={} y={}", p.x, p.y);
+                point.x = p.x.into();
+                point.y = p.y.into();
+                need_redraw = true;
+            }
+
+            // Change color on double tap
+            let now = Instant::now().duration_since_epoch().as_millis() as u32;
+            if let Some(Gesture::DoubleTap { .. }) = gestures.update(now, touches.get(0)) {
+                style_index = (style_index + 1) % COLOR_LIST.len();
+                need_redraw = true;
             }
         } else {
             error!("I2C error");
@@ -0,0 +1,299 @@
+//! Controller thresholds and timings
+//!
+//! `Ft3267Config` holds the sensitivity and timing registers written by
+//! `Ft3267::init_with()`. The defaults are the values used by `init()`; raise
+//! the thresholds for a noisier environment, lower them to detect touches
+//! through gloves.
+use embedded_hal::i2c::I2c;
+
+use crate::{Error, Ft3267, regs};
+
+/// Controller configuration, built with the `with_*()` methods.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ft3267Config {
+    touch_threshold: u8,
+    peak_threshold: u8,
+    focus_threshold: u8,
+    water_threshold: u8,
+    temperature_threshold: u8,
+    difference_threshold: u8,
+    monitor_delay: u8,
+    active_period: u8,
+    monitor_period: u8,
+}
+
+impl Default for Ft3267Config {
+    fn default() -> Self {
+        Ft3267Config {
+            touch_threshold: 70,
+            peak_threshold: 60,
+            focus_threshold: 16,
+            water_threshold: 60,
+            temperature_threshold: 10,
+            difference_threshold: 20,
+            monitor_delay: 2,
+            active_period: 12,
+            monitor_period: 40,
+        }
+    }
+}
+
+impl Ft3267Config {
+    /// Build the default configuration.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Touch detection threshold (THGROUP).
+    pub fn with_touch_threshold(mut self, value: u8) -> Self {
+        self.touch_threshold = value;
+        self
+    }
+
+    /// Valid touching peak detection threshold (THPEAK).
+    pub fn with_peak_threshold(mut self, value: u8) -> Self {
+        self.peak_threshold = value;
+        self
+    }
+
+    /// Touch focus threshold (THCAL).
+    pub fn with_focus_threshold(mut self, value: u8) -> Self {
+        self.focus_threshold = value;
+        self
+    }
+
+    /// Threshold when there is surface water (THWATER).
+    pub fn with_water_threshold(mut self, value: u8) -> Self {
+        self.water_threshold = value;
+        self
+    }
+
+    /// Threshold of temperature compensation (THTEMP).
+    pub fn with_temperature_threshold(mut self, value: u8) -> Self {
+        self.temperature_threshold = value;
+        self
+    }
+
+    /// Touch difference threshold (THDIFF).
+    pub fn with_difference_threshold(mut self, value: u8) -> Self {
+        self.difference_threshold = value;
+        self
+    }
+
+    /// Delay without touch before entering 'Monitor' status, in seconds
+    /// (TIME_ENTER_MONITOR).
+    pub fn with_monitor_delay(mut self, seconds: u8) -> Self {
+        self.monitor_delay = seconds;
+        self
+    }
+
+    /// Scan period in 'Active' status, in milliseconds (PERIODACTIVE).
+    pub fn with_active_period(mut self, ms: u8) -> Self {
+        self.active_period = ms;
+        self
+    }
+
+    /// Scan period in 'Monitor' status, in milliseconds (PERIODMONITOR).
+    pub fn with_monitor_period(mut self, ms: u8) -> Self {
+        self.monitor_period = ms;
+        self
+    }
+
+    /// Register and value pairs, in write order.
+    fn registers(&self) -> [(u8, u8); 9] {
+        [
+            (regs::FT3267_ID_G_THGROUP, self.touch_threshold),
+            (regs::FT3267_ID_G_THPEAK, self.peak_threshold),
+            (regs::FT3267_ID_G_THCAL, self.focus_threshold),
+            (regs::FT3267_ID_G_THWATER, self.water_threshold),
+            (regs::FT3267_ID_G_THTEMP, self.temperature_threshold),
+            (regs::FT3267_ID_G_THDIFF, self.difference_threshold),
+            (regs::FT3267_ID_G_TIME_ENTER_MONITOR, self.monitor_delay),
+            (regs::FT3267_ID_G_PERIODACTIVE, self.active_period),
+            (regs::FT3267_ID_G_PERIODMONITOR, self.monitor_period),
+        ]
+    }
+}
+
+impl Ft3267 {
+    /// Write the configuration registers, without checking them.
+    pub(crate) fn write_config<I2C: I2c>(
+        &self,
+        bus: &mut I2C,
+        config: &Ft3267Config,
+    ) -> Result<(), Error<I2C::Error>> {
+        for (register, value) in config.registers() {
+            self.write_register(bus, register, value)?;
+        }
+
+        Ok(())
+    }
+
+    /// Initialize the driver IC with a configuration.
+    ///
+    /// All the registers are written, then read back: if the controller did
+    /// not keep a value, `Error::ConfigMismatch` is returned for the first
+    /// one. The other values are applied anyway.
+    pub fn init_with<I2C: I2c>(
+        &self,
+        bus: &mut I2C,
+        config: &Ft3267Config,
+    ) -> Result<&Self, Error<I2C::Error>> {
+        self.write_config(bus, config)?;
+
+        for (register, written) in config.registers() {
+            let mut read = [0];
+            self.read_register(bus, register, &mut read)?;
+            if read[0] != written {
+                return Err(Error::ConfigMismatch {
+                    register,
+                    written,
+                    read: read[0],
+                });
+            }
+        }
+
+        Ok(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use core::convert::Infallible;
+    use std::vec::Vec;
+
+    use embedded_hal::i2c::{ErrorType, Operation};
+
+    use super::*;
+    use crate::Rotation;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum Access {
+        Write(u8, u8),
+        Read(u8),
+    }
+
+    /// Register file recording the accesses, with one register that ignores
+    /// writes.
+    struct FakeBus {
+        registers: [u8; 256],
+        read_only: Option<u8>,
+        pointer: u8,
+        log: Vec<Access>,
+    }
+
+    impl FakeBus {
+        fn new(read_only: Option<u8>) -> Self {
+            FakeBus {
+                registers: [0; 256],
+                read_only,
+                pointer: 0,
+                log: Vec::new(),
+            }
+        }
+    }
+
+    impl ErrorType for FakeBus {
+        type Error = Infallible;
+    }
+
+    impl I2c for FakeBus {
+        fn transaction(
+            &mut self,
+            _address: u8,
+            operations: &mut [Operation<'_>],
+        ) -> Result<(), Self::Error> {
+            for operation in operations {
+                match operation {
+                    Operation::Write(bytes) => {
+                        self.pointer = bytes[0];
+                        for &value in &bytes[1..] {
+                            self.log.push(Access::Write(self.pointer, value));
+                            if self.read_only != Some(self.pointer) {
+                                self.registers[self.pointer as usize] = value;
+                            }
+                            self.pointer = self.pointer.wrapping_add(1);
+                        }
+                    }
+                    Operation::Read(buffer) => {
+                        for value in buffer.iter_mut() {
+                            self.log.push(Access::Read(self.pointer));
+                            *value = self.registers[self.pointer as usize];
+                            self.pointer = self.pointer.wrapping_add(1);
+                        }
+                    }
+                }
+            }
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn write_order() {
+        let mut bus = FakeBus::new(None);
+        let config = Ft3267Config::new()
+            .with_touch_threshold(1)
+            .with_monitor_period(9);
+        Ft3267::new(Rotation::default())
+            .init_with(&mut bus, &config)
+            .unwrap();
+
+        let writes: Vec<_> = config
+            .registers()
+            .iter()
+            .map(|&(register, value)| Access::Write(register, value))
+            .collect();
+        let reads: Vec<_> = config
+            .registers()
+            .iter()
+            .map(|&(register, _)| Access::Read(register))
+            .collect();
+        assert_eq!(bus.log[..9], writes[..]);
+        assert_eq!(bus.log[9..], reads[..]);
+        assert_eq!(bus.log[0], Access::Write(regs::FT3267_ID_G_THGROUP, 1));
+        assert_eq!(
+            bus.log[8],
+            Access::Write(regs::FT3267_ID_G_PERIODMONITOR, 9)
+        );
+    }
+
+    #[test]
+    fn init_does_not_read_back() {
+        let mut bus = FakeBus::new(Some(regs::FT3267_ID_G_THPEAK));
+        Ft3267::new(Rotation::default()).init(&mut bus).unwrap();
+
+        assert_eq!(bus.log.len(), 9);
+        assert!(
+            bus.log
+                .iter()
+                .all(|access| matches!(access, Access::Write(..)))
+        );
+    }
+
+    #[test]
+    fn mismatch() {
+        let mut bus = FakeBus::new(Some(regs::FT3267_ID_G_THPEAK));
+        let ft3267 = Ft3267::new(Rotation::default());
+        let result = ft3267.init_with(&mut bus, &Ft3267Config::default());
+
+        assert_eq!(
+            result.err(),
+            Some(Error::ConfigMismatch {
+                register: regs::FT3267_ID_G_THPEAK,
+                written: 60,
+                read: 0,
+            })
+        );
+        // All the registers are written before the first read-back
+        let first_read = bus
+            .log
+            .iter()
+            .position(|access| matches!(access, Access::Read(_)))
+            .unwrap();
+        assert_eq!(first_read, 9);
+        assert_eq!(bus.registers[regs::FT3267_ID_G_PERIODMONITOR as usize], 40);
+    }
+}
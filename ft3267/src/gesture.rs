@@ -0,0 +1,648 @@
+//! Software gesture recognizer
+//!
+//! `GestureRecognizer` turns a stream of timestamped touch samples into tap,
+//! double tap, long press, drag and swipe gestures. It does not access the
+//! hardware: feed it with the first point of `Ft3267::read_touches()` (or
+//! `None` when untouched) on each poll, along with a millisecond timestamp.
+//!
+//! A tap is only reported once the double tap interval has elapsed, so the
+//! recognizer must keep being updated while the screen is untouched.
+use crate::{TouchEvent, TouchPoint};
+
+/// Gesture recognizer thresholds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GestureConfig {
+    /// Largest movement, in pixels, for a touch to still count as a tap or a
+    /// long press.
+    pub tap_max_distance: u16,
+    /// Longest duration of a tap, in milliseconds.
+    pub tap_max_duration: u32,
+    /// Longest interval between two taps of a double tap, in milliseconds.
+    /// Set to 0 to report taps without delay and disable double taps.
+    pub double_tap_interval: u32,
+    /// Duration after which a still touch is a long press, in milliseconds.
+    pub long_press_duration: u32,
+    /// Shortest movement of a swipe, in pixels.
+    pub swipe_min_distance: u16,
+    /// Longest duration of a swipe, in milliseconds. A slower movement is
+    /// only reported as a drag.
+    pub swipe_max_duration: u32,
+}
+
+impl Default for GestureConfig {
+    fn default() -> Self {
+        GestureConfig {
+            tap_max_distance: 10,
+            tap_max_duration: 250,
+            double_tap_interval: 300,
+            long_press_duration: 600,
+            swipe_min_distance: 50,
+            swipe_max_duration: 400,
+        }
+    }
+}
+
+/// Swipe direction, in screen coordinates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SwipeDirection {
+    /// Toward the top of the screen.
+    Up,
+    /// Toward the bottom of the screen.
+    Down,
+    /// Toward the left of the screen.
+    Left,
+    /// Toward the right of the screen.
+    Right,
+}
+
+/// Recognized gesture.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Gesture {
+    /// Short touch and release.
+    Tap { x: u16, y: u16 },
+    /// Two taps in a row at the same place.
+    DoubleTap { x: u16, y: u16 },
+    /// Still touch held longer than the long press duration. Reported while
+    /// the finger is still down.
+    LongPress { x: u16, y: u16 },
+    /// Finger moving, with the movement since the previous sample.
+    Drag { x: u16, y: u16, dx: i16, dy: i16 },
+    /// Finger lifted at the end of a drag that is not a swipe.
+    DragEnd { x: u16, y: u16 },
+    /// Fast movement, reported when the finger is lifted.
+    Swipe { direction: SwipeDirection },
+}
+
+/// Touch sample position and time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Sample {
+    x: u16,
+    y: u16,
+    time: u32,
+}
+
+impl Sample {
+    /// Squared distance to another sample.
+    fn distance2(&self, other: &Sample) -> i64 {
+        let dx = other.x as i64 - self.x as i64;
+        let dy = other.y as i64 - self.y as i64;
+
+        dx * dx + dy * dy
+    }
+
+    /// Milliseconds elapsed since an earlier sample.
+    fn elapsed_since(&self, earlier: &Sample) -> u32 {
+        self.time.wrapping_sub(earlier.time)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Idle,
+    /// Finger down, not moved beyond the tap distance.
+    Pressed {
+        start: Sample,
+        /// Earlier tap, waiting to know if this touch makes a double tap.
+        pending_tap: Option<Sample>,
+        long_press: bool,
+    },
+    /// Finger down and moving.
+    Dragging {
+        start: Sample,
+        last: Sample,
+        /// Position of the last reported drag.
+        reported: Sample,
+    },
+    /// Tap released, waiting for a second tap.
+    TapReleased {
+        tap: Sample,
+    },
+}
+
+/// Gesture state machine.
+#[derive(Debug, Clone)]
+pub struct GestureRecognizer {
+    config: GestureConfig,
+    state: State,
+}
+
+impl Default for GestureRecognizer {
+    fn default() -> Self {
+        Self::new(GestureConfig::default())
+    }
+}
+
+impl GestureRecognizer {
+    /// Build a new recognizer.
+    pub fn new(config: GestureConfig) -> Self {
+        GestureRecognizer {
+            config,
+            state: State::Idle,
+        }
+    }
+
+    /// Thresholds in use.
+    pub fn config(&self) -> &GestureConfig {
+        &self.config
+    }
+
+    /// Forget the gesture in progress.
+    pub fn reset(&mut self) {
+        self.state = State::Idle;
+    }
+
+    /// Feed a touch sample.
+    ///
+    /// time: timestamp in milliseconds, wrapping around is supported.
+    /// touch: current touch point, or `None` if the screen is untouched. A
+    /// point with a `TouchEvent::Up` event is also a release.
+    /// return the gesture recognized with this sample, if any.
+    pub fn update(&mut self, time: u32, touch: Option<&TouchPoint>) -> Option<Gesture> {
+        match touch {
+            Some(point) if point.event != TouchEvent::Up => self.touched(Sample {
+                x: point.x,
+                y: point.y,
+                time,
+            }),
+            Some(point) => self.released(Sample {
+                x: point.x,
+                y: point.y,
+                time,
+            }),
+            None => self.untouched(time),
+        }
+    }
+
+    fn is_still(&self, start: &Sample, sample: &Sample) -> bool {
+        let max = self.config.tap_max_distance as i64;
+
+        start.distance2(sample) <= max * max
+    }
+
+    fn touched(&mut self, sample: Sample) -> Option<Gesture> {
+        match self.state {
+            State::Idle => {
+                self.state = State::Pressed {
+                    start: sample,
+                    pending_tap: None,
+                    long_press: false,
+                };
+                None
+            }
+            State::TapReleased { tap } => {
+                let in_time = sample.elapsed_since(&tap) <= self.config.double_tap_interval;
+                if in_time && self.is_still(&tap, &sample) {
+                    self.state = State::Pressed {
+                        start: sample,
+                        pending_tap: Some(tap),
+                        long_press: false,
+                    };
+                    None
+                } else {
+                    // Too late or too far to be a double tap: report the
+                    // first tap now.
+                    self.state = State::Pressed {
+                        start: sample,
+                        pending_tap: None,
+                        long_press: false,
+                    };
+                    Some(Gesture::Tap { x: tap.x, y: tap.y })
+                }
+            }
+            State::Pressed {
+                start,
+                pending_tap,
+                long_press,
+            } => {
+                let moved = !self.is_still(&start, &sample);
+                let held = sample.elapsed_since(&start) >= self.config.long_press_duration;
+
+                if let Some(tap) = pending_tap.filter(|_| moved || held) {
+                    // Not a double tap: report the first tap, the drag or long
+                    // press is reported from the next sample.
+                    self.state = if moved {
+                        State::Dragging {
+                            start,
+                            last: sample,
+                            reported: start,
+                        }
+                    } else {
+                        State::Pressed {
+                            start,
+                            pending_tap: None,
+                            long_press,
+                        }
+                    };
+                    return Some(Gesture::Tap { x: tap.x, y: tap.y });
+                }
+
+                if moved {
+                    self.state = State::Dragging {
+                        start,
+                        last: sample,
+                        reported: sample,
+                    };
+                    Some(drag(&start, &sample))
+                } else if held && !long_press {
+                    self.state = State::Pressed {
+                        start,
+                        pending_tap,
+                        long_press: true,
+                    };
+                    Some(Gesture::LongPress {
+                        x: start.x,
+                        y: start.y,
+                    })
+                } else {
+                    None
+                }
+            }
+            State::Dragging {
+                start, reported, ..
+            } => {
+                self.state = State::Dragging {
+                    start,
+                    last: sample,
+                    reported: sample,
+                };
+                (sample.x != reported.x || sample.y != reported.y).then(|| drag(&reported, &sample))
+            }
+        }
+    }
+
+    fn released(&mut self, sample: Sample) -> Option<Gesture> {
+        match self.state {
+            State::Idle | State::TapReleased { .. } => None,
+            State::Pressed {
+                start,
+                pending_tap,
+                long_press,
+            } => {
+                self.state = State::Idle;
+
+                if !self.is_still(&start, &sample) {
+                    // Moved since the last sample: end of a drag. A pending
+                    // tap takes precedence, the movement is then dropped.
+                    if let Some(tap) = pending_tap {
+                        return Some(Gesture::Tap { x: tap.x, y: tap.y });
+                    }
+                    self.state = State::Dragging {
+                        start,
+                        last: sample,
+                        reported: start,
+                    };
+                    return self.released(sample);
+                }
+
+                if long_press || sample.elapsed_since(&start) > self.config.tap_max_duration {
+                    return pending_tap.map(|tap| Gesture::Tap { x: tap.x, y: tap.y });
+                }
+
+                if pending_tap.is_some() {
+                    Some(Gesture::DoubleTap {
+                        x: start.x,
+                        y: start.y,
+                    })
+                } else if self.config.double_tap_interval == 0 {
+                    Some(Gesture::Tap {
+                        x: start.x,
+                        y: start.y,
+                    })
+                } else {
+                    self.state = State::TapReleased {
+                        tap: Sample {
+                            time: sample.time,
+                            ..start
+                        },
+                    };
+                    None
+                }
+            }
+            State::Dragging { start, .. } => {
+                self.state = State::Idle;
+
+                let min = self.config.swipe_min_distance as i64;
+                let fast = sample.elapsed_since(&start) <= self.config.swipe_max_duration;
+                if fast && start.distance2(&sample) >= min * min {
+                    Some(Gesture::Swipe {
+                        direction: swipe_direction(&start, &sample),
+                    })
+                } else {
+                    Some(Gesture::DragEnd {
+                        x: sample.x,
+                        y: sample.y,
+                    })
+                }
+            }
+        }
+    }
+
+    fn untouched(&mut self, time: u32) -> Option<Gesture> {
+        match self.state {
+            State::Idle => None,
+            State::TapReleased { tap } => {
+                if time.wrapping_sub(tap.time) > self.config.double_tap_interval {
+                    self.state = State::Idle;
+                    Some(Gesture::Tap { x: tap.x, y: tap.y })
+                } else {
+                    None
+                }
+            }
+            State::Pressed { start, .. } => self.released(Sample { time, ..start }),
+            State::Dragging { last, .. } => self.released(Sample { time, ..last }),
+        }
+    }
+}
+
+fn drag(from: &Sample, to: &Sample) -> Gesture {
+    Gesture::Drag {
+        x: to.x,
+        y: to.y,
+        dx: (to.x as i32 - from.x as i32) as i16,
+        dy: (to.y as i32 - from.y as i32) as i16,
+    }
+}
+
+fn swipe_direction(start: &Sample, end: &Sample) -> SwipeDirection {
+    let dx = end.x as i32 - start.x as i32;
+    let dy = end.y as i32 - start.y as i32;
+
+    if dx.abs() >= dy.abs() {
+        if dx > 0 {
+            SwipeDirection::Right
+        } else {
+            SwipeDirection::Left
+        }
+    } else if dy > 0 {
+        SwipeDirection::Down
+    } else {
+        SwipeDirection::Up
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Feed a trace of `(time, touch)` samples, return the gestures with the
+    /// time they were recognized at.
+    fn run(
+        recognizer: &mut GestureRecognizer,
+        trace: &[(u32, Option<(u16, u16)>)],
+    ) -> ([(u32, Gesture); 8], usize) {
+        let mut gestures = [(0, Gesture::Tap { x: 0, y: 0 }); 8];
+        let mut len = 0;
+
+        for &(time, touch) in trace {
+            let point = touch.map(|(x, y)| TouchPoint {
+                id: 0,
+                event: TouchEvent::Contact,
+                x,
+                y,
+            });
+            if let Some(gesture) = recognizer.update(time, point.as_ref()) {
+                gestures[len] = (time, gesture);
+                len += 1;
+            }
+        }
+
+        (gestures, len)
+    }
+
+    fn check(trace: &[(u32, Option<(u16, u16)>)], expected: &[(u32, Gesture)]) {
+        let (gestures, len) = run(&mut GestureRecognizer::default(), trace);
+
+        assert_eq!(&gestures[..len], expected);
+    }
+
+    #[test]
+    fn tap() {
+        check(
+            &[
+                (0, Some((100, 100))),
+                (50, Some((103, 98))),
+                (100, None),
+                (400, None),
+                (401, None),
+                (500, None),
+            ],
+            &[(401, Gesture::Tap { x: 100, y: 100 })],
+        );
+    }
+
+    #[test]
+    fn tap_without_double_tap() {
+        let mut recognizer = GestureRecognizer::new(GestureConfig {
+            double_tap_interval: 0,
+            ..GestureConfig::default()
+        });
+        let (gestures, len) = run(&mut recognizer, &[(0, Some((10, 20))), (100, None)]);
+
+        assert_eq!(&gestures[..len], &[(100, Gesture::Tap { x: 10, y: 20 })]);
+    }
+
+    #[test]
+    fn double_tap() {
+        check(
+            &[
+                (0, Some((100, 100))),
+                (100, None),
+                (200, Some((102, 101))),
+                (300, None),
+                (1000, None),
+            ],
+            &[(300, Gesture::DoubleTap { x: 102, y: 101 })],
+        );
+    }
+
+    #[test]
+    fn taps_too_far_apart() {
+        check(
+            &[
+                (0, Some((100, 100))),
+                (100, None),
+                (200, Some((150, 100))),
+                (300, None),
+                (1000, None),
+            ],
+            &[
+                (200, Gesture::Tap { x: 100, y: 100 }),
+                (1000, Gesture::Tap { x: 150, y: 100 }),
+            ],
+        );
+    }
+
+    #[test]
+    fn long_press() {
+        check(
+            &[
+                (0, Some((100, 100))),
+                (599, Some((100, 100))),
+                (600, Some((101, 100))),
+                (700, Some((101, 100))),
+                (800, None),
+                (2000, None),
+            ],
+            &[(600, Gesture::LongPress { x: 100, y: 100 })],
+        );
+    }
+
+    #[test]
+    fn drag() {
+        check(
+            &[
+                (0, Some((100, 100))),
+                (500, Some((130, 100))),
+                (600, Some((130, 90))),
+                (700, Some((130, 90))),
+                (800, None),
+            ],
+            &[
+                (
+                    500,
+                    Gesture::Drag {
+                        x: 130,
+                        y: 100,
+                        dx: 30,
+                        dy: 0,
+                    },
+                ),
+                (
+                    600,
+                    Gesture::Drag {
+                        x: 130,
+                        y: 90,
+                        dx: 0,
+                        dy: -10,
+                    },
+                ),
+                (800, Gesture::DragEnd { x: 130, y: 90 }),
+            ],
+        );
+    }
+
+    #[test]
+    fn swipe() {
+        check(
+            &[(0, Some((100, 100))), (100, Some((100, 30))), (150, None)],
+            &[
+                (
+                    100,
+                    Gesture::Drag {
+                        x: 100,
+                        y: 30,
+                        dx: 0,
+                        dy: -70,
+                    },
+                ),
+                (
+                    150,
+                    Gesture::Swipe {
+                        direction: SwipeDirection::Up,
+                    },
+                ),
+            ],
+        );
+    }
+
+    #[test]
+    fn swipe_after_tap() {
+        // The second touch moves: the first tap is reported, then the swipe.
+        check(
+            &[
+                (0, Some((100, 100))),
+                (100, None),
+                (200, Some((100, 100))),
+                (250, Some((150, 100))),
+                (300, None),
+                (1000, None),
+            ],
+            &[
+                (250, Gesture::Tap { x: 100, y: 100 }),
+                (
+                    300,
+                    Gesture::Swipe {
+                        direction: SwipeDirection::Right,
+                    },
+                ),
+            ],
+        );
+    }
+
+    #[test]
+    fn drag_after_tap() {
+        // The movement of the sample reporting the tap is part of the next
+        // drag.
+        check(
+            &[
+                (0, Some((100, 100))),
+                (100, None),
+                (200, Some((100, 100))),
+                (250, Some((150, 100))),
+                (800, Some((150, 100))),
+                (900, None),
+            ],
+            &[
+                (250, Gesture::Tap { x: 100, y: 100 }),
+                (
+                    800,
+                    Gesture::Drag {
+                        x: 150,
+                        y: 100,
+                        dx: 50,
+                        dy: 0,
+                    },
+                ),
+                (900, Gesture::DragEnd { x: 150, y: 100 }),
+            ],
+        );
+    }
+
+    #[test]
+    fn moved_on_release() {
+        let mut recognizer = GestureRecognizer::default();
+        let release = TouchPoint {
+            id: 0,
+            event: TouchEvent::Up,
+            x: 40,
+            y: 100,
+        };
+        run(&mut recognizer, &[(0, Some((100, 100)))]);
+
+        assert_eq!(
+            recognizer.update(100, Some(&release)),
+            Some(Gesture::Swipe {
+                direction: SwipeDirection::Left
+            })
+        );
+        assert_eq!(recognizer.update(1000, None), None);
+    }
+
+    #[test]
+    fn timestamp_wrap() {
+        check(
+            &[(u32::MAX - 50, Some((100, 100))), (49, None), (400, None)],
+            &[(400, Gesture::Tap { x: 100, y: 100 })],
+        );
+    }
+
+    #[test]
+    fn large_thresholds() {
+        let mut recognizer = GestureRecognizer::new(GestureConfig {
+            tap_max_distance: u16::MAX,
+            swipe_min_distance: u16::MAX,
+            ..GestureConfig::default()
+        });
+        let (gestures, len) = run(
+            &mut recognizer,
+            &[
+                (0, Some((0, 0))),
+                (10, Some((u16::MAX, 0))),
+                (20, None),
+                (1000, None),
+            ],
+        );
+
+        assert_eq!(&gestures[..len], &[(1000, Gesture::Tap { x: 0, y: 0 })]);
+    }
+}
@@ -8,8 +8,22 @@
 #[allow(dead_code)]
 mod regs;
 
+mod config;
+mod gesture;
+mod pinch;
+mod power;
+mod rim;
+
 use embedded_hal::i2c::I2c;
 
+pub use config::Ft3267Config;
+pub use gesture::{Gesture, GestureConfig, GestureRecognizer, SwipeDirection};
+pub use pinch::{Pinch, PinchRecognizer};
+pub use power::PowerMode;
+pub use rim::{Direction, MIN_DEGREES_PER_STEP, RimRotation, RimRotationConfig};
+
+/// Gesture detected by the controller, see `Ft3267::gesture()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Ft3265Gesture {
     Ft3267GestureNone = 0x00,
     Ft3267GestureMoveUp = 0x10,
@@ -20,38 +34,186 @@ pub enum Ft3265Gesture {
     Ft3267GestureZomeOut = 0x49,
 }
 
+impl TryFrom<u8> for Ft3265Gesture {
+    /// Unknown gesture code.
+    type Error = u8;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0x00 => Ok(Ft3265Gesture::Ft3267GestureNone),
+            0x10 => Ok(Ft3265Gesture::Ft3267GestureMoveUp),
+            0x14 => Ok(Ft3265Gesture::Ft3267GestureMoveLeft),
+            0x18 => Ok(Ft3265Gesture::Ft3267GestureMoveDown),
+            0x1c => Ok(Ft3265Gesture::Ft3267GestureMoveRight),
+            0x48 => Ok(Ft3265Gesture::Ft3267GestureZomeIn),
+            0x49 => Ok(Ft3265Gesture::Ft3267GestureZomeOut),
+            _ => Err(value),
+        }
+    }
+}
+
+/// Touch driver error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error<E> {
+    /// I2C bus error.
+    I2c(E),
+    /// Gesture code read from the controller is unknown.
+    UnknownGesture(u8),
+    /// Power mode code read from the controller is unknown.
+    UnknownPowerMode(u8),
+    /// Touch point index is above the number of slots of the chip.
+    InvalidPointIndex,
+    /// Configuration register not holding the written value.
+    ConfigMismatch { register: u8, written: u8, read: u8 },
+}
+
+impl<E> From<E> for Error<E> {
+    fn from(error: E) -> Self {
+        Error::I2c(error)
+    }
+}
+
 /// FT3267 Driver.
 ///
 /// Use to interact with the FT3267.
 #[derive(Debug)]
 pub struct Ft3267 {
     address: u8,
-    rotation: u8,
+    rotation: Rotation,
+    mirror_x: bool,
+    mirror_y: bool,
+    width: u16,
+    height: u16,
+}
+
+/// Touch coordinates rotation, clockwise.
+///
+/// With a panel of `width` x `height` (native coordinates), a native point
+/// `(x, y)` is reported as:
+///  - `Deg0`: `(x, y)`
+///  - `Deg90`: `(height - 1 - y, x)`
+///  - `Deg180`: `(width - 1 - x, height - 1 - y)`
+///  - `Deg270`: `(y, width - 1 - x)`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Rotation {
+    /// No rotation.
+    #[default]
+    Deg0,
+    /// 90 degrees clockwise.
+    Deg90,
+    /// 180 degrees.
+    Deg180,
+    /// 270 degrees clockwise (90 degrees counterclockwise).
+    Deg270,
+}
+
+/// Default panel size, the M5Dial 240x240 round screen.
+pub const DEFAULT_PANEL_SIZE: (u16, u16) = (240, 240);
+
+/// Number of touch points tracked by the chip.
+pub const MAX_TOUCH_POINTS: usize = 5;
+
+/// Size of a touch point record in the register map.
+const TOUCH_POINT_REGS: usize = 6;
+
+/// Touch event of a point, from the event flag (bits 7:6 of XH).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TouchEvent {
+    /// Finger just pressed down.
+    Down,
+    /// Finger just lifted up.
+    Up,
+    /// Finger still in contact, e.g. dragging.
+    Contact,
+    /// No event.
+    #[default]
+    None,
+}
+
+impl From<u8> for TouchEvent {
+    /// Decode the event flag from the XH register.
+    fn from(xh: u8) -> Self {
+        match xh >> 6 {
+            0b00 => TouchEvent::Down,
+            0b01 => TouchEvent::Up,
+            0b10 => TouchEvent::Contact,
+            _ => TouchEvent::None,
+        }
+    }
 }
 
 /// Touch point coordinate.
 ///
-/// Returned by gFt3275.get_point().
-#[derive(Debug)]
+/// Returned by `Ft3267::get_point()` and `Ft3267::read_touches()`, in screen
+/// coordinates: the rotation and mirroring are already applied.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub struct TouchPoint {
-    /// Touch point id, used on multitouch to distinguish touch position.
+    /// Touch tracking id (bits 7:4 of YH), used on multitouch to follow a
+    /// finger from a read to the next.
     pub id: u8,
+    /// Touch event.
+    pub event: TouchEvent,
     /// Touch point X coordinate.
     pub x: u16,
     /// Touch point Y coordinate.
     pub y: u16,
 }
 
+/// Touch points read in a single transfer, see `Ft3267::read_touches()`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Touches {
+    points: [TouchPoint; MAX_TOUCH_POINTS],
+    len: usize,
+}
+
+impl Touches {
+    /// Number of touch points.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Query if the screen is untouched.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Get a touch point.
+    pub fn get(&self, index: usize) -> Option<&TouchPoint> {
+        self.as_slice().get(index)
+    }
+
+    /// Touch points as a slice.
+    pub fn as_slice(&self) -> &[TouchPoint] {
+        &self.points[..self.len]
+    }
+
+    /// Iterate over the touch points.
+    pub fn iter(&self) -> core::slice::Iter<'_, TouchPoint> {
+        self.as_slice().iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Touches {
+    type Item = &'a TouchPoint;
+    type IntoIter = core::slice::Iter<'a, TouchPoint>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
 impl Ft3267 {
     fn write_register<I2C: I2c>(
         &self,
         bus: &mut I2C,
         reg_addr: u8,
         reg_value: u8,
-    ) -> Result<(), I2C::Error> {
+    ) -> Result<(), Error<I2C::Error>> {
         let buffer: [u8; 2] = [reg_addr, reg_value];
 
-        bus.write(self.address, &buffer)
+        bus.write(self.address, &buffer)?;
+
+        Ok(())
     }
 
     fn read_register<I2C: I2c>(
@@ -59,50 +221,49 @@ impl Ft3267 {
         bus: &mut I2C,
         reg_addr: u8,
         buffer: &mut [u8],
-    ) -> Result<(), I2C::Error> {
+    ) -> Result<(), Error<I2C::Error>> {
         let addr_buffer: [u8; 1] = [reg_addr];
 
-        // TODO : Error handling
-        bus.write_read(self.address, &addr_buffer, buffer)
+        bus.write_read(self.address, &addr_buffer, buffer)?;
+
+        Ok(())
     }
 
     /// Build a new FT3257 driver
     ///
-    /// rotation: touch screen rotation
-    pub fn new(rotation: u8) -> Self {
+    /// rotation: touch screen rotation, applied to the reported coordinates.
+    pub fn new(rotation: Rotation) -> Self {
         Ft3267 {
             address: regs::FT3267_ADDR,
             rotation,
+            mirror_x: false,
+            mirror_y: false,
+            width: DEFAULT_PANEL_SIZE.0,
+            height: DEFAULT_PANEL_SIZE.1,
         }
     }
 
-    /// Initialize the driver IC.
-    pub fn init<I2C: I2c>(&self, bus: &mut I2C) -> Result<&Self, I2C::Error> {
-        self.write_register(bus, regs::FT3267_ID_G_THGROUP, 70)?;
-
-        // valid touching peak detect threshold
-        self.write_register(bus, regs::FT3267_ID_G_THPEAK, 60)?;
-
-        // Touch focus threshold
-        self.write_register(bus, regs::FT3267_ID_G_THCAL, 16)?;
-
-        // threshold when there is surface water
-        self.write_register(bus, regs::FT3267_ID_G_THWATER, 60)?;
-
-        // threshold of temperature compensation
-        self.write_register(bus, regs::FT3267_ID_G_THTEMP, 10)?;
-
-        // Touch difference threshold
-        self.write_register(bus, regs::FT3267_ID_G_THDIFF, 20)?;
-
-        // Delay to enter 'Monitor' status (s)
-        self.write_register(bus, regs::FT3267_ID_G_TIME_ENTER_MONITOR, 2)?;
+    /// Mirror the native X and/or Y axis, before the rotation.
+    pub fn with_mirror(mut self, mirror_x: bool, mirror_y: bool) -> Self {
+        self.mirror_x = mirror_x;
+        self.mirror_y = mirror_y;
+        self
+    }
 
-        // Period of 'Active' status (ms)
-        self.write_register(bus, regs::FT3267_ID_G_PERIODACTIVE, 12)?;
+    /// Set the panel size, in native coordinates.
+    ///
+    /// Defaults to `DEFAULT_PANEL_SIZE`.
+    pub fn with_panel_size(mut self, width: u16, height: u16) -> Self {
+        self.width = width;
+        self.height = height;
+        self
+    }
 
-        // Timer to enter 'idle' when in 'Monitor' (ms)
-        self.write_register(bus, regs::FT3267_ID_G_PERIODMONITOR, 40)?;
+    /// Initialize the driver IC with the default configuration.
+    ///
+    /// The registers are not read back, see `init_with()` to check them.
+    pub fn init<I2C: I2c>(&self, bus: &mut I2C) -> Result<&Self, Error<I2C::Error>> {
+        self.write_config(bus, &Ft3267Config::default())?;
 
         Ok(self)
     }
@@ -110,7 +271,7 @@ impl Ft3267 {
     /// Pool if the touch screen is touched.
     ///
     /// Returns the number of current touch points.
-    pub fn pool<I2C: I2c>(&self, bus: &mut I2C) -> Result<u8, I2C::Error> {
+    pub fn pool<I2C: I2c>(&self, bus: &mut I2C) -> Result<u8, Error<I2C::Error>> {
         let mut raw_data: [u8; 1] = [0];
         self.read_register(bus, regs::FT3267_TOUCH_POINTS, &mut raw_data)?;
         Ok(raw_data[0] & 0x0f)
@@ -119,7 +280,7 @@ impl Ft3267 {
     /// Query if the touch screen is touched. If touch screen
     /// is un-touched, return None. Return Some() with detected
     /// finger count if touched (supports multi-touch).
-    pub fn count<I2C: I2c>(&self, bus: &mut I2C) -> Result<Option<u8>, I2C::Error> {
+    pub fn count<I2C: I2c>(&self, bus: &mut I2C) -> Result<Option<u8>, Error<I2C::Error>> {
         let touch_count = self.pool(bus)?;
 
         if touch_count > 0 {
@@ -129,37 +290,92 @@ impl Ft3267 {
         }
     }
 
+    /// Read the gesture detected by the controller.
+    ///
+    /// Return `Error::UnknownGesture` if the code is not a known gesture.
+    pub fn gesture<I2C: I2c>(&self, bus: &mut I2C) -> Result<Ft3265Gesture, Error<I2C::Error>> {
+        let mut raw_data: [u8; 1] = [0];
+        self.read_register(bus, regs::FT3267_GESTURE_ID, &mut raw_data)?;
+
+        Ft3265Gesture::try_from(raw_data[0]).map_err(Error::UnknownGesture)
+    }
+
+    /// Read all active touch points in a single transfer.
+    ///
+    /// Reads the touch point count and the five point records at once.
+    pub fn read_touches<I2C: I2c>(&self, bus: &mut I2C) -> Result<Touches, Error<I2C::Error>> {
+        let mut buf: [u8; 1 + MAX_TOUCH_POINTS * TOUCH_POINT_REGS] =
+            [0; 1 + MAX_TOUCH_POINTS * TOUCH_POINT_REGS];
+        self.read_register(bus, regs::FT3267_TOUCH_POINTS, &mut buf)?;
+
+        // The count is not meaningful above the number of slots, e.g. while
+        // the chip boots.
+        let count = (buf[0] & 0x0f) as usize;
+        let len = if count > MAX_TOUCH_POINTS { 0 } else { count };
+
+        let mut touches = Touches::default();
+        for (point, record) in touches.points[..len]
+            .iter_mut()
+            .zip(buf[1..].chunks_exact(TOUCH_POINT_REGS))
+        {
+            *point = self.decode_point(&[record[0], record[1], record[2], record[3]]);
+        }
+        touches.len = len;
+
+        Ok(touches)
+    }
+
     /// Get the coordinate of a touch point
     ///
-    /// n touch point index.
-    /// return touch point coordinate.
-    pub fn get_point<I2C: I2c>(&self, bus: &mut I2C, n: u8) -> Result<TouchPoint, I2C::Error> {
+    /// n touch point index (0-4).
+    /// return touch point coordinate, or `Error::InvalidPointIndex`.
+    pub fn get_point<I2C: I2c>(
+        &self,
+        bus: &mut I2C,
+        n: u8,
+    ) -> Result<TouchPoint, Error<I2C::Error>> {
         let mut buf: [u8; 4] = [0; 4];
 
-        match n {
-            0 => {
-                self.read_register(bus, regs::FT3267_TOUCH1_XH, &mut buf)?;
-            }
-            1 => {
-                self.read_register(bus, regs::FT3267_TOUCH2_XH, &mut buf)?;
-            }
-            2 => {
-                self.read_register(bus, regs::FT3267_TOUCH3_XH, &mut buf)?;
-            }
-            3 => {
-                self.read_register(bus, regs::FT3267_TOUCH4_XH, &mut buf)?;
-            }
-            _ => {}
-        }
+        let reg_addr = match n {
+            0 => regs::FT3267_TOUCH1_XH,
+            1 => regs::FT3267_TOUCH2_XH,
+            2 => regs::FT3267_TOUCH3_XH,
+            3 => regs::FT3267_TOUCH4_XH,
+            4 => regs::FT3267_TOUCH5_XH,
+            _ => return Err(Error::InvalidPointIndex),
+        };
+        self.read_register(bus, reg_addr, &mut buf)?;
 
+        Ok(self.decode_point(&buf))
+    }
+
+    /// Decode a touch point from its XH, XL, YH and YL registers.
+    fn decode_point(&self, buf: &[u8; 4]) -> TouchPoint {
         let x = (((buf[0] & 0x0f) as u16) << 8) + buf[1] as u16;
         let y = (((buf[2] & 0x0f) as u16) << 8) + buf[3] as u16;
 
-        let coord = if self.rotation == 0 {
-            TouchPoint { id: n, x, y }
-        } else {
-            TouchPoint { id: n, x: y, y: x }
-        };
-        Ok(coord)
+        let id = buf[2] >> 4;
+        let event = TouchEvent::from(buf[0]);
+        let (x, y) = self.transform(x, y);
+
+        TouchPoint { id, event, x, y }
+    }
+
+    /// Map native coordinates to screen coordinates.
+    fn transform(&self, x: u16, y: u16) -> (u16, u16) {
+        let max_x = self.width.saturating_sub(1);
+        let max_y = self.height.saturating_sub(1);
+        let x = x.min(max_x);
+        let y = y.min(max_y);
+
+        let x = if self.mirror_x { max_x - x } else { x };
+        let y = if self.mirror_y { max_y - y } else { y };
+
+        match self.rotation {
+            Rotation::Deg0 => (x, y),
+            Rotation::Deg90 => (max_y - y, x),
+            Rotation::Deg180 => (max_x - x, max_y - y),
+            Rotation::Deg270 => (y, max_x - x),
+        }
     }
 }
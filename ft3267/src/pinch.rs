@@ -0,0 +1,202 @@
+//! Two finger pinch and rotate recognizer
+//!
+//! `PinchRecognizer` follows two fingers by their tracking ID and reports
+//! how the distance and the angle between them change, to zoom and rotate
+//! content. Feed it with the points of `Ft3267::read_touches()` on each
+//! poll.
+//!
+//! The first two fingers down are tracked until one of them is lifted, other
+//! fingers are ignored.
+use core::f32::consts::PI;
+
+use crate::rim::wrap_degrees;
+use crate::{TouchEvent, TouchPoint};
+
+/// Recognized pinch event.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Pinch {
+    /// Second finger down, with the point between both fingers.
+    Begin { x: u16, y: u16 },
+    /// Fingers sampled, with the point between them and the changes since the
+    /// previous sample.
+    ///
+    /// scale: distance ratio, above 1.0 when the fingers move apart.
+    /// rotation: angle change in degrees, positive clockwise.
+    Update {
+        x: u16,
+        y: u16,
+        scale: f32,
+        rotation: f32,
+    },
+    /// One of the tracked fingers lifted.
+    End,
+}
+
+/// Tracked finger pair.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Fingers {
+    first: u8,
+    second: u8,
+    x: u16,
+    y: u16,
+    /// Distance between the fingers, in pixels.
+    distance: f32,
+    /// Angle from the first to the second finger, in degrees.
+    angle: f32,
+}
+
+impl Fingers {
+    fn new(first: &TouchPoint, second: &TouchPoint) -> Self {
+        let dx = second.x as f32 - first.x as f32;
+        let dy = second.y as f32 - first.y as f32;
+
+        Fingers {
+            first: first.id,
+            second: second.id,
+            x: ((first.x as u32 + second.x as u32) / 2) as u16,
+            y: ((first.y as u32 + second.y as u32) / 2) as u16,
+            distance: libm::sqrtf(dx * dx + dy * dy),
+            angle: libm::atan2f(dy, dx) * 180.0 / PI,
+        }
+    }
+}
+
+/// Pinch state machine.
+#[derive(Debug, Clone, Default)]
+pub struct PinchRecognizer {
+    fingers: Option<Fingers>,
+}
+
+impl PinchRecognizer {
+    /// Build a new recognizer.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Query if two fingers are tracked.
+    pub fn is_active(&self) -> bool {
+        self.fingers.is_some()
+    }
+
+    /// Forget the pinch in progress, without reporting its end.
+    pub fn reset(&mut self) {
+        self.fingers = None;
+    }
+
+    /// Feed the touch points of a poll, e.g. `touches.as_slice()`.
+    ///
+    /// Return the pinch event of this sample, if any. An update is returned on
+    /// every sample while both fingers are down.
+    pub fn update(&mut self, touches: &[TouchPoint]) -> Option<Pinch> {
+        let mut down = touches.iter().filter(|point| point.event != TouchEvent::Up);
+
+        let Some(last) = self.fingers else {
+            let fingers = Fingers::new(down.next()?, down.next()?);
+            self.fingers = Some(fingers);
+
+            return Some(Pinch::Begin {
+                x: fingers.x,
+                y: fingers.y,
+            });
+        };
+
+        let first = down.clone().find(|point| point.id == last.first);
+        let second = down.find(|point| point.id == last.second);
+        let (Some(first), Some(second)) = (first, second) else {
+            self.fingers = None;
+            return Some(Pinch::End);
+        };
+
+        let fingers = Fingers::new(first, second);
+        self.fingers = Some(fingers);
+
+        Some(Pinch::Update {
+            x: fingers.x,
+            y: fingers.y,
+            // Fingers on the same spot: no meaningful ratio
+            scale: if last.distance > 0.0 {
+                fingers.distance / last.distance
+            } else {
+                1.0
+            },
+            rotation: wrap_degrees(fingers.angle - last.angle),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(id: u8, event: TouchEvent, x: u16, y: u16) -> TouchPoint {
+        TouchPoint { id, event, x, y }
+    }
+
+    #[test]
+    fn pinch_and_rotate() {
+        let mut recognizer = PinchRecognizer::new();
+
+        let one = [point(1, TouchEvent::Down, 100, 100)];
+        assert_eq!(recognizer.update(&one), None);
+
+        let two = [
+            point(1, TouchEvent::Contact, 100, 100),
+            point(2, TouchEvent::Down, 140, 100),
+        ];
+        assert_eq!(
+            recognizer.update(&two),
+            Some(Pinch::Begin { x: 120, y: 100 })
+        );
+        assert!(recognizer.is_active());
+
+        // Slots swapped, second finger a quarter turn clockwise and twice as far
+        let turned = [
+            point(2, TouchEvent::Contact, 100, 180),
+            point(1, TouchEvent::Contact, 100, 100),
+        ];
+        let Some(Pinch::Update {
+            x,
+            y,
+            scale,
+            rotation,
+        }) = recognizer.update(&turned)
+        else {
+            panic!("no update");
+        };
+        assert_eq!((x, y), (100, 140));
+        assert!((scale - 2.0).abs() < 1e-4);
+        assert!((rotation - 90.0).abs() < 1e-3);
+
+        let lifted = [
+            point(2, TouchEvent::Up, 100, 180),
+            point(1, TouchEvent::Contact, 100, 100),
+        ];
+        assert_eq!(recognizer.update(&lifted), Some(Pinch::End));
+        assert!(!recognizer.is_active());
+        assert_eq!(recognizer.update(&[]), None);
+    }
+
+    #[test]
+    fn fingers_on_the_same_spot() {
+        let mut recognizer = PinchRecognizer::new();
+        let same = [
+            point(1, TouchEvent::Down, 50, 50),
+            point(2, TouchEvent::Down, 50, 50),
+        ];
+        recognizer.update(&same);
+
+        let apart = [
+            point(1, TouchEvent::Contact, 50, 50),
+            point(2, TouchEvent::Contact, 60, 50),
+        ];
+        assert_eq!(
+            recognizer.update(&apart),
+            Some(Pinch::Update {
+                x: 55,
+                y: 50,
+                scale: 1.0,
+                rotation: 0.0
+            })
+        );
+    }
+}
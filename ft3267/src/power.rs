@@ -0,0 +1,123 @@
+//! Power modes
+//!
+//! In 'Active' status the controller scans every active period. After the
+//! monitor delay without touch, it can switch itself to 'Monitor' status
+//! (auto monitor), which scans at the slower monitor period and returns to
+//! 'Active' on touch. 'Hibernate' stops scanning altogether, until the
+//! controller is woken up by a low pulse on its INT pin, see `wake()`.
+use embedded_hal::delay::DelayNs;
+use embedded_hal::digital::OutputPin;
+use embedded_hal::i2c::I2c;
+
+use crate::{Error, Ft3267, regs};
+
+/// Controller power mode (PMODE).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PowerMode {
+    /// Full scan rate.
+    Active = 0x00,
+    /// Reduced scan rate, back to active on touch.
+    Monitor = 0x01,
+    /// Scanning stopped, lowest current. Left with `Ft3267::wake()`.
+    Hibernate = 0x03,
+}
+
+impl TryFrom<u8> for PowerMode {
+    /// Unknown power mode code.
+    type Error = u8;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0x00 => Ok(PowerMode::Active),
+            0x01 => Ok(PowerMode::Monitor),
+            0x03 => Ok(PowerMode::Hibernate),
+            _ => Err(value),
+        }
+    }
+}
+
+/// ID_G_CTRL value keeping the controller in 'Active' status.
+const CTRL_KEEP_ACTIVE: u8 = 0x00;
+/// ID_G_CTRL value switching to 'Monitor' status when untouched.
+const CTRL_AUTO_MONITOR: u8 = 0x01;
+
+/// Duration of the INT low pulse waking the controller up, in milliseconds.
+const WAKE_PULSE_MS: u32 = 5;
+/// Delay for the controller to start after the wake-up pulse, in
+/// milliseconds.
+const WAKE_STARTUP_MS: u32 = 200;
+
+impl Ft3267 {
+    /// Get the current power mode.
+    ///
+    /// Return `Error::UnknownPowerMode` if the code is not a known mode.
+    pub fn power_mode<I2C: I2c>(&self, bus: &mut I2C) -> Result<PowerMode, Error<I2C::Error>> {
+        let mut raw_data: [u8; 1] = [0];
+        self.read_register(bus, regs::FT3267_ID_G_PMODE, &mut raw_data)?;
+
+        PowerMode::try_from(raw_data[0]).map_err(Error::UnknownPowerMode)
+    }
+
+    /// Set the power mode.
+    ///
+    /// Once in `PowerMode::Hibernate`, the controller no longer answers on
+    /// the bus until woken up with `wake()` (or a hardware reset).
+    pub fn set_power_mode<I2C: I2c>(
+        &self,
+        bus: &mut I2C,
+        mode: PowerMode,
+    ) -> Result<(), Error<I2C::Error>> {
+        self.write_register(bus, regs::FT3267_ID_G_PMODE, mode as u8)
+    }
+
+    /// Enable or disable the automatic switch to 'Monitor' status when the
+    /// screen is untouched (ID_G_CTRL).
+    pub fn set_auto_monitor<I2C: I2c>(
+        &self,
+        bus: &mut I2C,
+        enabled: bool,
+    ) -> Result<(), Error<I2C::Error>> {
+        let value = if enabled {
+            CTRL_AUTO_MONITOR
+        } else {
+            CTRL_KEEP_ACTIVE
+        };
+
+        self.write_register(bus, regs::FT3267_ID_G_CTRL, value)
+    }
+
+    /// Set the delay without touch before the automatic switch to 'Monitor'
+    /// status, in seconds (TIME_ENTER_MONITOR).
+    ///
+    /// This overrides the monitor delay of the `Ft3267Config` used at
+    /// initialization.
+    pub fn set_monitor_timeout<I2C: I2c>(
+        &self,
+        bus: &mut I2C,
+        seconds: u8,
+    ) -> Result<(), Error<I2C::Error>> {
+        self.write_register(bus, regs::FT3267_ID_G_TIME_ENTER_MONITOR, seconds)
+    }
+
+    /// Wake the controller up from `PowerMode::Hibernate`.
+    ///
+    /// int: the controller INT pin, configured as an output (open drain or
+    /// push-pull). It is pulled low for a few milliseconds then released
+    /// high: reconfigure it as an input afterward to receive touch
+    /// interrupts.
+    ///
+    /// The controller restarts in 'Active' status, call `init()` again to
+    /// restore the configuration.
+    pub fn wake<P: OutputPin, D: DelayNs>(
+        &self,
+        int: &mut P,
+        delay: &mut D,
+    ) -> Result<(), P::Error> {
+        int.set_low()?;
+        delay.delay_ms(WAKE_PULSE_MS);
+        int.set_high()?;
+        delay.delay_ms(WAKE_STARTUP_MS);
+
+        Ok(())
+    }
+}
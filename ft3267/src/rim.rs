@@ -0,0 +1,364 @@
+//! Rim rotation recognizer
+//!
+//! On a round panel, sliding a finger along the edge is the touch equivalent
+//! of turning a knob. `RimRotation` converts touch points to an angle around
+//! the panel center, and reports a step each time the finger has turned by
+//! the configured angle, as a `Direction` like a rotary encoder.
+//!
+//! Enable the `rotary-encoder-hal` feature to convert `Direction` into
+//! `rotary_encoder_hal::Direction`, so touch rotation and a physical encoder
+//! can share the same code.
+use core::f32::consts::PI;
+
+use crate::{TouchEvent, TouchPoint};
+
+/// Rotation step direction.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Direction {
+    /// Finger turned clockwise.
+    Clockwise,
+    /// Finger turned counterclockwise.
+    CounterClockwise,
+    /// No step.
+    #[default]
+    None,
+}
+
+#[cfg(feature = "rotary-encoder-hal")]
+impl From<Direction> for rotary_encoder_hal::Direction {
+    fn from(direction: Direction) -> Self {
+        match direction {
+            Direction::Clockwise => rotary_encoder_hal::Direction::Clockwise,
+            Direction::CounterClockwise => rotary_encoder_hal::Direction::CounterClockwise,
+            Direction::None => rotary_encoder_hal::Direction::None,
+        }
+    }
+}
+
+/// Smallest rotation angle of a step, in degrees.
+pub const MIN_DEGREES_PER_STEP: f32 = 1.0;
+
+/// Rim rotation recognizer settings.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RimRotationConfig {
+    /// Rotation center X, in screen coordinates.
+    pub center_x: u16,
+    /// Rotation center Y, in screen coordinates.
+    pub center_y: u16,
+    /// Touches closer to the center than this radius, in pixels, are
+    /// ignored.
+    pub min_radius: u16,
+    /// Touches farther from the center than this radius, in pixels, are
+    /// ignored.
+    pub max_radius: u16,
+    /// Rotation angle of a step, in degrees, clamped to
+    /// `MIN_DEGREES_PER_STEP..=360.0`.
+    pub degrees_per_step: f32,
+}
+
+impl Default for RimRotationConfig {
+    /// Outer band of the M5Dial 240x240 round screen, 15 degrees per step.
+    fn default() -> Self {
+        RimRotationConfig {
+            center_x: 120,
+            center_y: 120,
+            min_radius: 80,
+            max_radius: 140,
+            degrees_per_step: 15.0,
+        }
+    }
+}
+
+/// Rim rotation state machine.
+#[derive(Debug, Clone)]
+pub struct RimRotation {
+    config: RimRotationConfig,
+    /// Angle of the previous touch on the rim, in degrees.
+    last_angle: Option<f32>,
+    /// Rotation not yet reported, in degrees, positive clockwise.
+    pending: f32,
+}
+
+impl Default for RimRotation {
+    fn default() -> Self {
+        Self::new(RimRotationConfig::default())
+    }
+}
+
+impl RimRotation {
+    /// Build a new recognizer.
+    ///
+    /// A step angle out of range is clamped, NaN is replaced by the default.
+    pub fn new(mut config: RimRotationConfig) -> Self {
+        config.degrees_per_step = if config.degrees_per_step.is_nan() {
+            RimRotationConfig::default().degrees_per_step
+        } else {
+            config.degrees_per_step.clamp(MIN_DEGREES_PER_STEP, 360.0)
+        };
+
+        RimRotation {
+            config,
+            last_angle: None,
+            pending: 0.0,
+        }
+    }
+
+    /// Settings in use.
+    pub fn config(&self) -> &RimRotationConfig {
+        &self.config
+    }
+
+    /// Forget the rotation in progress.
+    pub fn reset(&mut self) {
+        self.last_angle = None;
+        self.pending = 0.0;
+    }
+
+    /// Feed a touch sample, `None` if the screen is untouched.
+    ///
+    /// A point with `TouchEvent::Up` is handled like `None`: the finger is
+    /// already lifted, its position is not part of the rotation.
+    ///
+    /// Return at most one step per call: a fast rotation is reported over the
+    /// next calls, even once the finger is lifted.
+    pub fn update(&mut self, touch: Option<&TouchPoint>) -> Direction {
+        let angle = touch
+            .filter(|point| point.event != TouchEvent::Up)
+            .and_then(|point| self.angle(point));
+        match angle {
+            Some(angle) => {
+                if let Some(last_angle) = self.last_angle {
+                    self.pending += wrap_degrees(angle - last_angle);
+                }
+                self.last_angle = Some(angle);
+            }
+            None => {
+                // Off the rim: drop the partial step.
+                self.last_angle = None;
+                let step = self.config.degrees_per_step;
+                self.pending = libm::truncf(self.pending / step) * step;
+            }
+        }
+
+        let step = self.config.degrees_per_step;
+        if self.pending >= step {
+            self.pending -= step;
+            Direction::Clockwise
+        } else if self.pending <= -step {
+            self.pending += step;
+            Direction::CounterClockwise
+        } else {
+            Direction::None
+        }
+    }
+
+    /// Angle of a point around the center in degrees, or `None` if outside
+    /// of the radius band.
+    ///
+    /// Screen Y points down, so the angle increases clockwise.
+    fn angle(&self, point: &TouchPoint) -> Option<f32> {
+        let dx = point.x as f32 - self.config.center_x as f32;
+        let dy = point.y as f32 - self.config.center_y as f32;
+        let radius2 = dx * dx + dy * dy;
+
+        let min = self.config.min_radius as f32;
+        let max = self.config.max_radius as f32;
+        if radius2 < min * min || radius2 > max * max {
+            return None;
+        }
+
+        Some(libm::atan2f(dy, dx) * 180.0 / PI)
+    }
+}
+
+/// Wrap an angle difference to -180..=180 degrees.
+pub(crate) fn wrap_degrees(angle: f32) -> f32 {
+    if angle > 180.0 {
+        angle - 360.0
+    } else if angle < -180.0 {
+        angle + 360.0
+    } else {
+        angle
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Point on the default rim, at an angle in degrees.
+    fn rim(degrees: f32) -> TouchPoint {
+        at(degrees, 110.0, TouchEvent::Contact)
+    }
+
+    fn at(degrees: f32, radius: f32, event: TouchEvent) -> TouchPoint {
+        let angle = degrees * PI / 180.0;
+        TouchPoint {
+            id: 0,
+            event,
+            x: libm::roundf(120.0 + radius * libm::cosf(angle)) as u16,
+            y: libm::roundf(120.0 + radius * libm::sinf(angle)) as u16,
+        }
+    }
+
+    fn steps(rim_rotation: &mut RimRotation, touches: &[Option<TouchPoint>]) -> [Direction; 8] {
+        let mut steps = [Direction::None; 8];
+        for (step, touch) in steps.iter_mut().zip(touches) {
+            *step = rim_rotation.update(touch.as_ref());
+        }
+
+        steps
+    }
+
+    #[test]
+    fn clockwise() {
+        let mut rim_rotation = RimRotation::default();
+        assert_eq!(
+            steps(
+                &mut rim_rotation,
+                &[
+                    Some(rim(0.0)),
+                    Some(rim(20.0)),
+                    Some(rim(25.0)),
+                    Some(rim(40.0))
+                ]
+            )[..4],
+            [
+                Direction::None,
+                Direction::Clockwise,
+                Direction::None,
+                Direction::Clockwise
+            ]
+        );
+    }
+
+    #[test]
+    fn counterclockwise() {
+        let mut rim_rotation = RimRotation::default();
+        assert_eq!(
+            steps(
+                &mut rim_rotation,
+                &[
+                    Some(rim(0.0)),
+                    Some(rim(-20.0)),
+                    Some(rim(-25.0)),
+                    Some(rim(-40.0))
+                ]
+            )[..4],
+            [
+                Direction::None,
+                Direction::CounterClockwise,
+                Direction::None,
+                Direction::CounterClockwise
+            ]
+        );
+    }
+
+    #[test]
+    fn radius_band() {
+        let mut rim_rotation = RimRotation::default();
+        let inner = at(20.0, 50.0, TouchEvent::Contact);
+        let outer = at(20.0, 150.0, TouchEvent::Contact);
+        assert_eq!(
+            steps(
+                &mut rim_rotation,
+                &[
+                    Some(rim(0.0)),
+                    Some(inner),
+                    Some(rim(20.0)),
+                    Some(outer),
+                    Some(rim(40.0))
+                ]
+            ),
+            [Direction::None; 8]
+        );
+    }
+
+    #[test]
+    fn crossing_left_side() {
+        let mut rim_rotation = RimRotation::default();
+        assert_eq!(
+            steps(&mut rim_rotation, &[Some(rim(170.0)), Some(rim(190.0))])[..2],
+            [Direction::None, Direction::Clockwise]
+        );
+
+        rim_rotation.reset();
+        assert_eq!(
+            steps(&mut rim_rotation, &[Some(rim(-170.0)), Some(rim(-190.0))])[..2],
+            [Direction::None, Direction::CounterClockwise]
+        );
+    }
+
+    #[test]
+    fn partial_step_dropped() {
+        let mut rim_rotation = RimRotation::default();
+        assert_eq!(
+            steps(
+                &mut rim_rotation,
+                &[
+                    Some(rim(0.0)),
+                    Some(rim(10.0)),
+                    None,
+                    Some(rim(0.0)),
+                    Some(rim(10.0))
+                ]
+            ),
+            [Direction::None; 8]
+        );
+    }
+
+    #[test]
+    fn lift_event() {
+        let mut rim_rotation = RimRotation::default();
+        assert_eq!(
+            steps(
+                &mut rim_rotation,
+                &[
+                    Some(rim(0.0)),
+                    Some(rim(10.0)),
+                    Some(at(20.0, 110.0, TouchEvent::Up)),
+                    Some(rim(0.0)),
+                    Some(rim(10.0))
+                ]
+            ),
+            [Direction::None; 8]
+        );
+    }
+
+    #[test]
+    fn queued_steps() {
+        // 50 degrees in one sample: 3 steps, one per call
+        let mut rim_rotation = RimRotation::default();
+        assert_eq!(
+            steps(
+                &mut rim_rotation,
+                &[Some(rim(0.0)), Some(rim(50.0)), None, None, None]
+            )[..5],
+            [
+                Direction::None,
+                Direction::Clockwise,
+                Direction::Clockwise,
+                Direction::Clockwise,
+                Direction::None
+            ]
+        );
+    }
+
+    #[test]
+    fn degrees_per_step_clamped() {
+        let step = |degrees_per_step| {
+            RimRotation::new(RimRotationConfig {
+                degrees_per_step,
+                ..Default::default()
+            })
+            .config()
+            .degrees_per_step
+        };
+
+        assert_eq!(step(f32::NAN), 15.0);
+        assert_eq!(step(0.0), MIN_DEGREES_PER_STEP);
+        assert_eq!(step(-30.0), MIN_DEGREES_PER_STEP);
+        assert_eq!(step(f32::INFINITY), 360.0);
+        assert_eq!(step(90.0), 90.0);
+    }
+}
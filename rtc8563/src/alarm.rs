@@ -0,0 +1,151 @@
+//! Alarm support
+//!
+//! The BM8563 alarm compares minutes, hours, day and weekday against the
+//! current time. Each field can be enabled individually; the alarm flag (AF)
+//! is raised when all enabled fields match. The alarm registers live in the
+//! battery backed register file, so an alarm survives a CPU reset.
+use embedded_hal::i2c::I2c;
+
+use crate::{Error, Interrupts, MINUTE_ALARM_REG, Rtc8563, bcd2byte, byte2bcd};
+
+/// Alarm enable bit (AE) is active low: set means the field is ignored.
+const ALARM_DISABLE: u8 = 0x80;
+
+/// Alarm setting.
+///
+/// Fields set to `None` are disabled and ignored when matching. An alarm with
+/// all fields disabled never fires.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Alarm {
+    /// Minutes to match (0-59).
+    pub minutes: Option<u8>,
+    /// Hours to match (0-23).
+    pub hours: Option<u8>,
+    /// Day of the month to match (1-31).
+    pub day: Option<u8>,
+    /// Weekday to match (0-6).
+    pub week_day: Option<u8>,
+}
+
+impl Alarm {
+    /// Check that all enabled fields are in range.
+    pub fn is_valid(&self) -> bool {
+        self.minutes.is_none_or(|minutes| minutes < 60)
+            && self.hours.is_none_or(|hours| hours < 24)
+            && self.day.is_none_or(|day| (1..=31).contains(&day))
+            && self.week_day.is_none_or(|week_day| week_day < 7)
+    }
+
+    /// Encode to the alarm registers (0x09-0x0C).
+    fn to_registers(self) -> [u8; 4] {
+        fn encode(field: Option<u8>, mask: u8) -> u8 {
+            match field {
+                Some(value) => byte2bcd(value) & mask,
+                None => ALARM_DISABLE,
+            }
+        }
+
+        [
+            encode(self.minutes, 0x7f),
+            encode(self.hours, 0x3f),
+            encode(self.day, 0x3f),
+            encode(self.week_day, 0x07),
+        ]
+    }
+
+    /// Validate and encode an alarm write transfer.
+    pub(crate) fn write_buffer<E>(&self) -> Result<[u8; 5], Error<E>> {
+        if !self.is_valid() {
+            return Err(Error::InvalidAlarm);
+        }
+
+        let regs = self.to_registers();
+
+        Ok([MINUTE_ALARM_REG, regs[0], regs[1], regs[2], regs[3]])
+    }
+
+    /// Decode from the alarm registers (0x09-0x0C).
+    pub(crate) fn from_registers(regs: &[u8; 4]) -> Option<Self> {
+        // Outer `None` on invalid BCD, inner `None` on disabled field.
+        fn decode(reg: u8, mask: u8) -> Option<Option<u8>> {
+            if reg & ALARM_DISABLE == ALARM_DISABLE {
+                Some(None)
+            } else {
+                bcd2byte(reg & mask).map(Some)
+            }
+        }
+
+        let alarm = Alarm {
+            minutes: decode(regs[0], 0x7f)?,
+            hours: decode(regs[1], 0x3f)?,
+            day: decode(regs[2], 0x3f)?,
+            week_day: decode(regs[3], 0x07)?,
+        };
+
+        alarm.is_valid().then_some(alarm)
+    }
+}
+
+impl Rtc8563 {
+    /// Program the alarm.
+    ///
+    /// Any pending alarm flag is acknowledged, so that `alarm_fired()` only
+    /// reports a match of the new setting. The alarm interrupt enable is left
+    /// untouched, see `set_alarm_interrupt()`. Return `Error::InvalidAlarm`
+    /// if an enabled field is out of range.
+    pub fn set_alarm<I2C: I2c>(
+        &self,
+        bus: &mut I2C,
+        alarm: &Alarm,
+    ) -> Result<(), Error<I2C::Error>> {
+        let buffer = alarm.write_buffer()?;
+
+        bus.write(self.address, &buffer)?;
+        self.acknowledge_alarm(bus)
+    }
+
+    /// Read back the programmed alarm.
+    pub fn get_alarm<I2C: I2c>(&self, bus: &mut I2C) -> Result<Alarm, Error<I2C::Error>> {
+        let addr_buffer: [u8; 1] = [MINUTE_ALARM_REG];
+        let mut buffer: [u8; 4] = [0; 4];
+
+        bus.write_read(self.address, &addr_buffer, &mut buffer)?;
+
+        Alarm::from_registers(&buffer).ok_or(Error::InvalidRegister)
+    }
+
+    /// Disable the alarm.
+    ///
+    /// Disable all alarm fields, the alarm interrupt and clear the alarm flag.
+    pub fn clear_alarm<I2C: I2c>(&self, bus: &mut I2C) -> Result<(), Error<I2C::Error>> {
+        self.set_alarm(bus, &Alarm::default())?;
+        self.disable_interrupts(bus, Interrupts::ALARM)
+    }
+
+    /// Clear the alarm flag (AF).
+    ///
+    /// This also release the INT pin if the alarm interrupt is enabled.
+    pub fn acknowledge_alarm<I2C: I2c>(&self, bus: &mut I2C) -> Result<(), Error<I2C::Error>> {
+        self.clear_flags(bus, Interrupts::ALARM)
+    }
+
+    /// Query if the alarm has fired since it was last acknowledged.
+    pub fn alarm_fired<I2C: I2c>(&self, bus: &mut I2C) -> Result<bool, Error<I2C::Error>> {
+        Ok(self.get_flags(bus)?.alarm)
+    }
+
+    /// Enable or disable the alarm interrupt (AIE).
+    ///
+    /// When enabled, the INT pin is asserted while the alarm flag is set.
+    pub fn set_alarm_interrupt<I2C: I2c>(
+        &self,
+        bus: &mut I2C,
+        enable: bool,
+    ) -> Result<(), Error<I2C::Error>> {
+        if enable {
+            self.enable_interrupts(bus, Interrupts::ALARM)
+        } else {
+            self.disable_interrupts(bus, Interrupts::ALARM)
+        }
+    }
+}
@@ -0,0 +1,526 @@
+//! Async driver
+//!
+//! `Rtc8563Async` offers the same API as `Rtc8563`, over an
+//! `embedded_hal_async::i2c::I2c` bus. Register encoding and validation are
+//! shared with the blocking driver.
+//!
+//! Enabled with the `async` feature.
+use embedded_hal_async::i2c::I2c;
+
+use crate::control::interrupt_mode_bits;
+use crate::timer::TIMER_ENABLE;
+use crate::{
+    Alarm, CLKOUT_CONTROL_REG, CONTROL_STATUS_1_REG, CONTROL_STATUS_2_REG, CenturyPolicy, ClkOut,
+    ClockIntegrity, Control1, Control2, DAYS_REG, Date, DateTime, DriftCompensation, Error,
+    InterruptMode, Interrupts, MINUTE_ALARM_REG, Rtc8563, SECONDS_REG, TIMER_CONTROL_REG,
+    TIMER_REG, Time, TimerConfig, TimerSource, decode_if_valid, integrity_from_seconds,
+    time_write_buffer, update_control2,
+};
+
+/// Async BM8563 driver.
+///
+/// See `Rtc8563` for the documentation of each method.
+pub struct Rtc8563Async {
+    rtc: Rtc8563,
+}
+
+impl From<Rtc8563> for Rtc8563Async {
+    fn from(rtc: Rtc8563) -> Self {
+        Self { rtc }
+    }
+}
+
+impl Rtc8563Async {
+    pub fn new(address: u8) -> Self {
+        Rtc8563::new(address).into()
+    }
+
+    /// Compute the weekday when writing a date.
+    pub fn with_auto_week_day(self, enable: bool) -> Self {
+        self.rtc.with_auto_week_day(enable).into()
+    }
+
+    /// Set the interpretation of the century bit.
+    pub fn with_century_policy(self, century: CenturyPolicy) -> Self {
+        self.rtc.with_century_policy(century).into()
+    }
+
+    /// Initialize chip.
+    ///
+    /// Control/status 2 is kept, see `Rtc8563::init()`.
+    pub async fn init<I2C: I2c>(&self, bus: &mut I2C) -> Result<(), Error<I2C::Error>> {
+        self.set_control1(bus, &Control1::default()).await
+    }
+
+    /// Get the time.
+    pub async fn get_time<I2C: I2c>(&self, bus: &mut I2C) -> Result<Time, Error<I2C::Error>> {
+        let buffer: [u8; 3] = self.read_registers(bus, SECONDS_REG).await?;
+
+        Time::from_registers(&buffer).ok_or(Error::InvalidRegister)
+    }
+
+    /// Get the time, if the clock integrity is guaranteed.
+    pub async fn get_valid_time<I2C: I2c>(
+        &self,
+        bus: &mut I2C,
+    ) -> Result<Option<Time>, Error<I2C::Error>> {
+        let buffer: [u8; 3] = self.read_registers(bus, SECONDS_REG).await?;
+
+        decode_if_valid(buffer[0], Time::from_registers(&buffer))
+    }
+
+    /// Query the clock integrity (VL flag).
+    pub async fn clock_integrity<I2C: I2c>(
+        &self,
+        bus: &mut I2C,
+    ) -> Result<ClockIntegrity, Error<I2C::Error>> {
+        let seconds = self.read_register(bus, SECONDS_REG).await?;
+
+        Ok(integrity_from_seconds(seconds))
+    }
+
+    /// Set the time.
+    pub async fn set_time<I2C: I2c>(
+        &self,
+        bus: &mut I2C,
+        time: &Time,
+    ) -> Result<(), Error<I2C::Error>> {
+        let buffer = time_write_buffer(time)?;
+
+        bus.write(self.rtc.address, &buffer).await?;
+
+        Ok(())
+    }
+
+    /// Get the date.
+    pub async fn get_date<I2C: I2c>(&self, bus: &mut I2C) -> Result<Date, Error<I2C::Error>> {
+        let buffer: [u8; 4] = self.read_registers(bus, DAYS_REG).await?;
+
+        Date::from_registers(&buffer, self.rtc.century).ok_or(Error::InvalidRegister)
+    }
+
+    /// Set the date.
+    pub async fn set_date<I2C: I2c>(
+        &self,
+        bus: &mut I2C,
+        date: &Date,
+    ) -> Result<(), Error<I2C::Error>> {
+        let buffer = self.rtc.date_write_buffer(date)?;
+
+        bus.write(self.rtc.address, &buffer).await?;
+
+        Ok(())
+    }
+
+    /// Get the date and time in a single transfer.
+    pub async fn get_datetime<I2C: I2c>(
+        &self,
+        bus: &mut I2C,
+    ) -> Result<DateTime, Error<I2C::Error>> {
+        let buffer: [u8; 7] = self.read_registers(bus, SECONDS_REG).await?;
+
+        DateTime::from_registers(&buffer, self.rtc.century).ok_or(Error::InvalidRegister)
+    }
+
+    /// Get the date and time, if the clock integrity is guaranteed.
+    pub async fn get_valid_datetime<I2C: I2c>(
+        &self,
+        bus: &mut I2C,
+    ) -> Result<Option<DateTime>, Error<I2C::Error>> {
+        let buffer: [u8; 7] = self.read_registers(bus, SECONDS_REG).await?;
+
+        decode_if_valid(
+            buffer[0],
+            DateTime::from_registers(&buffer, self.rtc.century),
+        )
+    }
+
+    /// Set the date and time in a single transfer, with the clock stopped.
+    pub async fn set_datetime<I2C: I2c>(
+        &self,
+        bus: &mut I2C,
+        datetime: &DateTime,
+    ) -> Result<(), Error<I2C::Error>> {
+        let buffer = self.rtc.datetime_write_buffer(datetime)?;
+
+        let control = self.get_control1(bus).await?;
+        self.set_control1(
+            bus,
+            &Control1 {
+                stop: true,
+                ..control
+            },
+        )
+        .await?;
+
+        // Always restart the clock, even if the transfer failed.
+        let result = bus.write(self.rtc.address, &buffer).await;
+        self.set_control1(
+            bus,
+            &Control1 {
+                stop: false,
+                ..control
+            },
+        )
+        .await?;
+
+        Ok(result?)
+    }
+
+    /// Get the date and time as seconds since the Unix epoch.
+    pub async fn get_unix_time<I2C: I2c>(&self, bus: &mut I2C) -> Result<i64, Error<I2C::Error>> {
+        Ok(self.get_datetime(bus).await?.to_unix_timestamp())
+    }
+
+    /// Set the date and time from seconds since the Unix epoch.
+    pub async fn set_unix_time<I2C: I2c>(
+        &self,
+        bus: &mut I2C,
+        timestamp: i64,
+    ) -> Result<(), Error<I2C::Error>> {
+        let datetime = DateTime::from_unix_timestamp(timestamp).ok_or(Error::InvalidDate)?;
+
+        self.set_datetime(bus, &datetime).await
+    }
+
+    /// Get the date and time as seconds since the Unix epoch, corrected for
+    /// the drift.
+    pub async fn get_compensated_unix_time<I2C: I2c>(
+        &self,
+        bus: &mut I2C,
+        compensation: &DriftCompensation,
+    ) -> Result<i64, Error<I2C::Error>> {
+        Ok(compensation.correct(self.get_unix_time(bus).await?))
+    }
+
+    /// Write the drift corrected time to the RTC, if it is off by a second or
+    /// more, and synchronize the compensation on it.
+    pub async fn adjust_drift<I2C: I2c>(
+        &self,
+        bus: &mut I2C,
+        compensation: &mut DriftCompensation,
+    ) -> Result<i64, Error<I2C::Error>> {
+        let rtc = self.get_unix_time(bus).await?;
+        let corrected = compensation.correct(rtc);
+
+        if corrected != rtc {
+            self.set_unix_time(bus, corrected).await?;
+            compensation.synchronize(corrected, corrected);
+        }
+
+        Ok(corrected - rtc)
+    }
+
+    /// Program the alarm.
+    pub async fn set_alarm<I2C: I2c>(
+        &self,
+        bus: &mut I2C,
+        alarm: &Alarm,
+    ) -> Result<(), Error<I2C::Error>> {
+        let buffer = alarm.write_buffer()?;
+
+        bus.write(self.rtc.address, &buffer).await?;
+        self.acknowledge_alarm(bus).await
+    }
+
+    /// Read back the programmed alarm.
+    pub async fn get_alarm<I2C: I2c>(&self, bus: &mut I2C) -> Result<Alarm, Error<I2C::Error>> {
+        let buffer: [u8; 4] = self.read_registers(bus, MINUTE_ALARM_REG).await?;
+
+        Alarm::from_registers(&buffer).ok_or(Error::InvalidRegister)
+    }
+
+    /// Disable the alarm.
+    pub async fn clear_alarm<I2C: I2c>(&self, bus: &mut I2C) -> Result<(), Error<I2C::Error>> {
+        self.set_alarm(bus, &Alarm::default()).await?;
+        self.disable_interrupts(bus, Interrupts::ALARM).await
+    }
+
+    /// Clear the alarm flag (AF).
+    pub async fn acknowledge_alarm<I2C: I2c>(
+        &self,
+        bus: &mut I2C,
+    ) -> Result<(), Error<I2C::Error>> {
+        self.clear_flags(bus, Interrupts::ALARM).await
+    }
+
+    /// Query if the alarm has fired since it was last acknowledged.
+    pub async fn alarm_fired<I2C: I2c>(&self, bus: &mut I2C) -> Result<bool, Error<I2C::Error>> {
+        Ok(self.get_flags(bus).await?.alarm)
+    }
+
+    /// Enable or disable the alarm interrupt (AIE).
+    pub async fn set_alarm_interrupt<I2C: I2c>(
+        &self,
+        bus: &mut I2C,
+        enable: bool,
+    ) -> Result<(), Error<I2C::Error>> {
+        if enable {
+            self.enable_interrupts(bus, Interrupts::ALARM).await
+        } else {
+            self.disable_interrupts(bus, Interrupts::ALARM).await
+        }
+    }
+
+    /// Configure the countdown timer, stopped.
+    pub async fn set_timer<I2C: I2c>(
+        &self,
+        bus: &mut I2C,
+        config: &TimerConfig,
+    ) -> Result<(), Error<I2C::Error>> {
+        let buffer = config.write_buffer();
+
+        bus.write(self.rtc.address, &buffer).await?;
+
+        Ok(())
+    }
+
+    /// Get the source clock of the countdown timer.
+    pub async fn get_timer_source<I2C: I2c>(
+        &self,
+        bus: &mut I2C,
+    ) -> Result<TimerSource, Error<I2C::Error>> {
+        let control = self.read_register(bus, TIMER_CONTROL_REG).await?;
+
+        Ok(TimerSource::from_bits(control))
+    }
+
+    /// Start the countdown timer.
+    pub async fn start_timer<I2C: I2c>(&self, bus: &mut I2C) -> Result<(), Error<I2C::Error>> {
+        let control = self.read_register(bus, TIMER_CONTROL_REG).await?;
+
+        self.write_register(bus, TIMER_CONTROL_REG, control | TIMER_ENABLE)
+            .await
+    }
+
+    /// Stop the countdown timer.
+    pub async fn stop_timer<I2C: I2c>(&self, bus: &mut I2C) -> Result<(), Error<I2C::Error>> {
+        let control = self.read_register(bus, TIMER_CONTROL_REG).await?;
+
+        self.write_register(bus, TIMER_CONTROL_REG, control & !TIMER_ENABLE)
+            .await
+    }
+
+    /// Query if the countdown timer is running.
+    pub async fn is_timer_running<I2C: I2c>(
+        &self,
+        bus: &mut I2C,
+    ) -> Result<bool, Error<I2C::Error>> {
+        let control = self.read_register(bus, TIMER_CONTROL_REG).await?;
+
+        Ok(control & TIMER_ENABLE == TIMER_ENABLE)
+    }
+
+    /// Read the remaining count before the next timer event.
+    pub async fn get_timer_value<I2C: I2c>(&self, bus: &mut I2C) -> Result<u8, Error<I2C::Error>> {
+        self.read_register(bus, TIMER_REG).await
+    }
+
+    /// Query if the timer has fired since it was last acknowledged.
+    pub async fn timer_fired<I2C: I2c>(&self, bus: &mut I2C) -> Result<bool, Error<I2C::Error>> {
+        Ok(self.get_flags(bus).await?.timer)
+    }
+
+    /// Clear the timer flag (TF).
+    pub async fn acknowledge_timer<I2C: I2c>(
+        &self,
+        bus: &mut I2C,
+    ) -> Result<(), Error<I2C::Error>> {
+        self.clear_flags(bus, Interrupts::TIMER).await
+    }
+
+    /// Enable or disable the timer interrupt (TIE).
+    pub async fn set_timer_interrupt<I2C: I2c>(
+        &self,
+        bus: &mut I2C,
+        enable: bool,
+    ) -> Result<(), Error<I2C::Error>> {
+        if enable {
+            self.enable_interrupts(bus, Interrupts::TIMER).await
+        } else {
+            self.disable_interrupts(bus, Interrupts::TIMER).await
+        }
+    }
+
+    /// Set the CLKOUT pin frequency.
+    pub async fn set_clkout<I2C: I2c>(
+        &self,
+        bus: &mut I2C,
+        clkout: ClkOut,
+    ) -> Result<(), Error<I2C::Error>> {
+        self.write_register(bus, CLKOUT_CONTROL_REG, clkout.to_register())
+            .await
+    }
+
+    /// Get the CLKOUT pin frequency.
+    pub async fn get_clkout<I2C: I2c>(&self, bus: &mut I2C) -> Result<ClkOut, Error<I2C::Error>> {
+        let reg = self.read_register(bus, CLKOUT_CONTROL_REG).await?;
+
+        Ok(ClkOut::from_register(reg))
+    }
+
+    /// Get control/status 1.
+    pub async fn get_control1<I2C: I2c>(
+        &self,
+        bus: &mut I2C,
+    ) -> Result<Control1, Error<I2C::Error>> {
+        let reg = self.read_register(bus, CONTROL_STATUS_1_REG).await?;
+
+        Ok(Control1::from_register(reg))
+    }
+
+    /// Set control/status 1.
+    pub async fn set_control1<I2C: I2c>(
+        &self,
+        bus: &mut I2C,
+        control: &Control1,
+    ) -> Result<(), Error<I2C::Error>> {
+        self.write_register(bus, CONTROL_STATUS_1_REG, control.to_register())
+            .await
+    }
+
+    /// Stop the clock (STOP bit).
+    pub async fn stop_clock<I2C: I2c>(&self, bus: &mut I2C) -> Result<(), Error<I2C::Error>> {
+        let control = self.get_control1(bus).await?;
+
+        self.set_control1(
+            bus,
+            &Control1 {
+                stop: true,
+                ..control
+            },
+        )
+        .await
+    }
+
+    /// Restart the clock.
+    pub async fn start_clock<I2C: I2c>(&self, bus: &mut I2C) -> Result<(), Error<I2C::Error>> {
+        let control = self.get_control1(bus).await?;
+
+        self.set_control1(
+            bus,
+            &Control1 {
+                stop: false,
+                ..control
+            },
+        )
+        .await
+    }
+
+    /// Query if the clock is running (STOP bit cleared).
+    pub async fn is_clock_running<I2C: I2c>(
+        &self,
+        bus: &mut I2C,
+    ) -> Result<bool, Error<I2C::Error>> {
+        Ok(!self.get_control1(bus).await?.stop)
+    }
+
+    /// Get control/status 2.
+    pub async fn get_control2<I2C: I2c>(
+        &self,
+        bus: &mut I2C,
+    ) -> Result<Control2, Error<I2C::Error>> {
+        let reg = self.read_register(bus, CONTROL_STATUS_2_REG).await?;
+
+        Ok(Control2::from_register(reg))
+    }
+
+    /// Get the pending interrupt flags.
+    pub async fn get_flags<I2C: I2c>(
+        &self,
+        bus: &mut I2C,
+    ) -> Result<Interrupts, Error<I2C::Error>> {
+        Ok(self.get_control2(bus).await?.flags)
+    }
+
+    /// Clear the selected interrupt flags.
+    pub async fn clear_flags<I2C: I2c>(
+        &self,
+        bus: &mut I2C,
+        flags: Interrupts,
+    ) -> Result<(), Error<I2C::Error>> {
+        self.update_control2(bus, 0, flags.flag_bits()).await
+    }
+
+    /// Enable the selected interrupts.
+    pub async fn enable_interrupts<I2C: I2c>(
+        &self,
+        bus: &mut I2C,
+        interrupts: Interrupts,
+    ) -> Result<(), Error<I2C::Error>> {
+        self.update_control2(bus, interrupts.enable_bits(), 0).await
+    }
+
+    /// Disable the selected interrupts.
+    pub async fn disable_interrupts<I2C: I2c>(
+        &self,
+        bus: &mut I2C,
+        interrupts: Interrupts,
+    ) -> Result<(), Error<I2C::Error>> {
+        self.update_control2(bus, 0, interrupts.enable_bits()).await
+    }
+
+    /// Set the timer interrupt mode (TI_TP).
+    pub async fn set_interrupt_mode<I2C: I2c>(
+        &self,
+        bus: &mut I2C,
+        mode: InterruptMode,
+    ) -> Result<(), Error<I2C::Error>> {
+        let (set, clear) = interrupt_mode_bits(mode);
+
+        self.update_control2(bus, set, clear).await
+    }
+
+    async fn update_control2<I2C: I2c>(
+        &self,
+        bus: &mut I2C,
+        set: u8,
+        clear: u8,
+    ) -> Result<(), Error<I2C::Error>> {
+        let ctrl2 = self.read_register(bus, CONTROL_STATUS_2_REG).await?;
+
+        self.write_register(
+            bus,
+            CONTROL_STATUS_2_REG,
+            update_control2(ctrl2, set, clear),
+        )
+        .await
+    }
+
+    async fn write_register<I2C: I2c>(
+        &self,
+        bus: &mut I2C,
+        reg_addr: u8,
+        reg_value: u8,
+    ) -> Result<(), Error<I2C::Error>> {
+        let buffer: [u8; 2] = [reg_addr, reg_value];
+
+        bus.write(self.rtc.address, &buffer).await?;
+
+        Ok(())
+    }
+
+    async fn read_register<I2C: I2c>(
+        &self,
+        bus: &mut I2C,
+        reg_addr: u8,
+    ) -> Result<u8, Error<I2C::Error>> {
+        let buffer: [u8; 1] = self.read_registers(bus, reg_addr).await?;
+
+        Ok(buffer[0])
+    }
+
+    async fn read_registers<I2C: I2c, const N: usize>(
+        &self,
+        bus: &mut I2C,
+        reg_addr: u8,
+    ) -> Result<[u8; N], Error<I2C::Error>> {
+        let addr_buffer: [u8; 1] = [reg_addr];
+        let mut buffer: [u8; N] = [0; N];
+
+        bus.write_read(self.rtc.address, &addr_buffer, &mut buffer)
+            .await?;
+
+        Ok(buffer)
+    }
+}
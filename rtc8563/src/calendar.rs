@@ -0,0 +1,119 @@
+//! Calendar arithmetic on `Date`
+//!
+//! All computations use the proleptic Gregorian calendar. The weekday is
+//! numbered from 0 (Sunday) to 6 (Saturday).
+use core::cmp::Ordering;
+
+use crate::Date;
+use crate::unix::{civil_from_days, days_from_civil, weekday_from_days};
+
+pub(crate) fn is_leap_year(year: i16) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+pub(crate) fn days_in_month(year: i16, month: u8) -> u8 {
+    match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        _ => 0,
+    }
+}
+
+impl Date {
+    /// Build a date, with the weekday computed.
+    ///
+    /// Return `None` if the date is not valid.
+    pub fn new(year: i16, month: u8, day: u8) -> Option<Self> {
+        let date = Date {
+            week_day: 0,
+            month,
+            day,
+            year,
+        }
+        .with_weekday();
+
+        date.is_valid().then_some(date)
+    }
+
+    /// Query if the year is a leap year.
+    pub fn is_leap_year(&self) -> bool {
+        is_leap_year(self.year)
+    }
+
+    /// Number of days in the month, or 0 if the month is invalid.
+    pub fn days_in_month(&self) -> u8 {
+        days_in_month(self.year, self.month)
+    }
+
+    /// Weekday computed from the calendar (0 = Sunday).
+    ///
+    /// The `week_day` field is ignored.
+    pub fn weekday(&self) -> u8 {
+        weekday_from_days(self.days_since_epoch())
+    }
+
+    /// Copy of the date with the `week_day` field computed from the calendar.
+    pub fn with_weekday(self) -> Self {
+        Date {
+            week_day: self.weekday(),
+            ..self
+        }
+    }
+
+    /// Date a number of days later, with the weekday computed.
+    ///
+    /// Return `None` if the result is out of the range supported by the chip.
+    pub fn add_days(&self, days: i32) -> Option<Self> {
+        let (year, month, day) = civil_from_days(self.days_since_epoch() + days as i64);
+        let date = Date {
+            week_day: 0,
+            month,
+            day,
+            year: i16::try_from(year).ok()?,
+        }
+        .with_weekday();
+
+        date.is_valid().then_some(date)
+    }
+
+    /// Date a number of days earlier, with the weekday computed.
+    ///
+    /// Return `None` if the result is out of the range supported by the chip.
+    pub fn sub_days(&self, days: i32) -> Option<Self> {
+        self.add_days(days.checked_neg()?)
+    }
+
+    /// Number of days from `other` to this date.
+    ///
+    /// Negative if `other` is later than this date.
+    pub fn days_since(&self, other: &Date) -> i32 {
+        (self.days_since_epoch() - other.days_since_epoch()) as i32
+    }
+
+    fn days_since_epoch(&self) -> i64 {
+        days_from_civil(self.year as i64, self.month, self.day)
+    }
+}
+
+/// Dates are compared on year, month and day. The weekday is ignored.
+impl PartialEq for Date {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Date {}
+
+impl PartialOrd for Date {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Date {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.year, self.month, self.day).cmp(&(other.year, other.month, other.day))
+    }
+}
@@ -0,0 +1,73 @@
+//! Conversions from and to the `chrono` crate types
+//!
+//! Enabled with the `chrono` feature.
+use chrono::{Datelike, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
+
+use crate::{ConversionError, Date, DateTime, Time};
+
+impl TryFrom<Date> for NaiveDate {
+    type Error = ConversionError;
+
+    fn try_from(date: Date) -> Result<Self, Self::Error> {
+        NaiveDate::from_ymd_opt(date.year as i32, date.month as u32, date.day as u32)
+            .ok_or(ConversionError)
+    }
+}
+
+impl TryFrom<NaiveDate> for Date {
+    type Error = ConversionError;
+
+    /// Fails if the year is out of the range supported by the chip.
+    fn try_from(date: NaiveDate) -> Result<Self, Self::Error> {
+        let date = Date {
+            week_day: date.weekday().num_days_from_sunday() as u8,
+            month: date.month() as u8,
+            day: date.day() as u8,
+            year: i16::try_from(date.year()).map_err(|_| ConversionError)?,
+        };
+
+        date.is_valid().then_some(date).ok_or(ConversionError)
+    }
+}
+
+impl TryFrom<Time> for NaiveTime {
+    type Error = ConversionError;
+
+    fn try_from(time: Time) -> Result<Self, Self::Error> {
+        NaiveTime::from_hms_opt(time.hours as u32, time.minutes as u32, time.seconds as u32)
+            .ok_or(ConversionError)
+    }
+}
+
+impl From<NaiveTime> for Time {
+    /// Sub-second precision is truncated.
+    fn from(time: NaiveTime) -> Self {
+        Time {
+            hours: time.hour() as u8,
+            minutes: time.minute() as u8,
+            seconds: time.second() as u8,
+        }
+    }
+}
+
+impl TryFrom<DateTime> for NaiveDateTime {
+    type Error = ConversionError;
+
+    fn try_from(datetime: DateTime) -> Result<Self, Self::Error> {
+        Ok(NaiveDateTime::new(
+            datetime.date.try_into()?,
+            datetime.time.try_into()?,
+        ))
+    }
+}
+
+impl TryFrom<NaiveDateTime> for DateTime {
+    type Error = ConversionError;
+
+    fn try_from(datetime: NaiveDateTime) -> Result<Self, Self::Error> {
+        Ok(DateTime {
+            date: datetime.date().try_into()?,
+            time: datetime.time().into(),
+        })
+    }
+}
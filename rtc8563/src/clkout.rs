@@ -0,0 +1,132 @@
+//! CLKOUT pin control
+//!
+//! The BM8563 can output a square wave derived from its 32.768 kHz crystal on
+//! the CLKOUT pin. At 1 Hz, it is derived from the same divider chain as the
+//! seconds counter, which makes it a convenient tick source for a clock face,
+//! see `SecondTick`.
+use embedded_hal::digital::InputPin;
+use embedded_hal::i2c::I2c;
+
+use crate::{CLKOUT_CONTROL_REG, Error, Rtc8563};
+
+/// CLKOUT enable bit (FE) of the CLKOUT control register.
+const CLKOUT_ENABLE: u8 = 0x80;
+
+/// CLKOUT pin frequency.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClkOut {
+    /// CLKOUT is disabled (high impedance).
+    Disabled,
+    /// 32.768 kHz
+    Hz32768,
+    /// 1024 Hz
+    Hz1024,
+    /// 32 Hz
+    Hz32,
+    /// 1 Hz
+    Hz1,
+}
+
+impl ClkOut {
+    pub(crate) fn to_register(self) -> u8 {
+        match self {
+            ClkOut::Disabled => 0x00,
+            ClkOut::Hz32768 => CLKOUT_ENABLE,
+            ClkOut::Hz1024 => CLKOUT_ENABLE | 0x01,
+            ClkOut::Hz32 => CLKOUT_ENABLE | 0x02,
+            ClkOut::Hz1 => CLKOUT_ENABLE | 0x03,
+        }
+    }
+
+    pub(crate) fn from_register(reg: u8) -> Self {
+        if reg & CLKOUT_ENABLE == 0 {
+            return ClkOut::Disabled;
+        }
+
+        match reg & 0x03 {
+            0x00 => ClkOut::Hz32768,
+            0x01 => ClkOut::Hz1024,
+            0x02 => ClkOut::Hz32,
+            _ => ClkOut::Hz1,
+        }
+    }
+}
+
+impl Rtc8563 {
+    /// Set the CLKOUT pin frequency.
+    pub fn set_clkout<I2C: I2c>(
+        &self,
+        bus: &mut I2C,
+        clkout: ClkOut,
+    ) -> Result<(), Error<I2C::Error>> {
+        self.write_register(bus, CLKOUT_CONTROL_REG, clkout.to_register())
+    }
+
+    /// Get the CLKOUT pin frequency.
+    pub fn get_clkout<I2C: I2c>(&self, bus: &mut I2C) -> Result<ClkOut, Error<I2C::Error>> {
+        let reg = self.read_register(bus, CLKOUT_CONTROL_REG)?;
+
+        Ok(ClkOut::from_register(reg))
+    }
+}
+
+/// Second tick from the CLKOUT pin.
+///
+/// Detect the rising edges of CLKOUT configured at 1 Hz (see `ClkOut::Hz1`)
+/// on an input pin. `poll()` returns `true` once per second, so the time only
+/// needs to be read from the chip when it has changed.
+///
+/// ```
+/// use embedded_hal::digital::InputPin;
+/// use embedded_hal::i2c::I2c;
+/// use rtc8563::{ClkOut, Error, Rtc8563, SecondTick, Time};
+///
+/// fn show_time<I2C: I2c, P: InputPin>(
+///     rtc: &Rtc8563,
+///     bus: &mut I2C,
+///     clkout: P,
+///     mut show: impl FnMut(Time),
+/// ) -> Result<(), Error<I2C::Error>> {
+///     rtc.set_clkout(bus, ClkOut::Hz1)?;
+///     let mut tick = SecondTick::new(clkout);
+///     loop {
+///         if tick.poll().unwrap_or(false) {
+///             show(rtc.get_time(bus)?);
+///         }
+///     }
+/// }
+/// ```
+pub struct SecondTick<P> {
+    pin: P,
+    last_level: bool,
+}
+
+impl<P: InputPin> SecondTick<P> {
+    /// Build a new tick detector on the pin connected to CLKOUT.
+    ///
+    /// The pin should be pulled up, as CLKOUT is an open-drain output.
+    pub fn new(pin: P) -> Self {
+        Self {
+            pin,
+            // Wait for a low level first, so no tick is reported on start.
+            last_level: true,
+        }
+    }
+
+    /// Poll the pin.
+    ///
+    /// Return `true` if a rising edge occurred since the last call. Must be
+    /// called at least twice per second to not miss ticks.
+    pub fn poll(&mut self) -> Result<bool, P::Error> {
+        let level = self.pin.is_high()?;
+        let tick = level && !self.last_level;
+        self.last_level = level;
+
+        Ok(tick)
+    }
+
+    /// Release the input pin.
+    pub fn release(self) -> P {
+        self.pin
+    }
+}
@@ -0,0 +1,257 @@
+//! Control/status registers
+//!
+//! Control/status 1 (0x00) holds the STOP bit, which freezes the clock, and
+//! the test bits. Control/status 2 (0x01) holds the interrupt enables, the
+//! alarm (AF) and timer (TF) flags, and the timer interrupt mode (TI_TP).
+//! The INT pin is asserted while an enabled flag is set, so `get_flags()`
+//! tells why it fired.
+use embedded_hal::i2c::I2c;
+
+use crate::{
+    CONTROL_STATUS_1_REG, CONTROL_STATUS_2_REG, CTRL1_STOP, CTRL1_TEST1, CTRL1_TESTC, CTRL2_AF,
+    CTRL2_AIE, CTRL2_TF, CTRL2_TI_TP, CTRL2_TIE, Error, Rtc8563,
+};
+
+/// Control/status 1 register.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Control1 {
+    /// Clock stopped (STOP): the divider chain is held in reset and the time
+    /// registers no longer count.
+    pub stop: bool,
+    /// EXT_CLK test mode (TEST1). Must be `false` in normal operation.
+    pub test_mode: bool,
+    /// Power-on reset override (TESTC). Must be `false` in normal operation.
+    pub power_on_reset_override: bool,
+}
+
+impl Control1 {
+    pub(crate) fn to_register(self) -> u8 {
+        let mut reg = 0;
+        if self.stop {
+            reg |= CTRL1_STOP;
+        }
+        if self.test_mode {
+            reg |= CTRL1_TEST1;
+        }
+        if self.power_on_reset_override {
+            reg |= CTRL1_TESTC;
+        }
+
+        reg
+    }
+
+    pub(crate) fn from_register(reg: u8) -> Self {
+        Control1 {
+            stop: reg & CTRL1_STOP != 0,
+            test_mode: reg & CTRL1_TEST1 != 0,
+            power_on_reset_override: reg & CTRL1_TESTC != 0,
+        }
+    }
+}
+
+/// Timer interrupt mode (TI_TP).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum InterruptMode {
+    /// INT is asserted while the timer flag is set, until acknowledged.
+    #[default]
+    Level,
+    /// INT pulses on each timer event, whatever the timer flag.
+    Pulse,
+}
+
+/// Interrupt sources, used both for the interrupt enables and the flags.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Interrupts {
+    /// Alarm (AIE / AF).
+    pub alarm: bool,
+    /// Countdown timer (TIE / TF).
+    pub timer: bool,
+}
+
+impl Interrupts {
+    /// No source.
+    pub const NONE: Interrupts = Interrupts {
+        alarm: false,
+        timer: false,
+    };
+    /// Alarm only.
+    pub const ALARM: Interrupts = Interrupts {
+        alarm: true,
+        timer: false,
+    };
+    /// Countdown timer only.
+    pub const TIMER: Interrupts = Interrupts {
+        alarm: false,
+        timer: true,
+    };
+    /// Both sources.
+    pub const ALL: Interrupts = Interrupts {
+        alarm: true,
+        timer: true,
+    };
+
+    /// Query if at least one source is set.
+    pub fn any(&self) -> bool {
+        self.alarm || self.timer
+    }
+
+    /// Interrupt enable bits (AIE, TIE).
+    pub(crate) fn enable_bits(self) -> u8 {
+        (if self.alarm { CTRL2_AIE } else { 0 }) | (if self.timer { CTRL2_TIE } else { 0 })
+    }
+
+    /// Flag bits (AF, TF).
+    pub(crate) fn flag_bits(self) -> u8 {
+        (if self.alarm { CTRL2_AF } else { 0 }) | (if self.timer { CTRL2_TF } else { 0 })
+    }
+
+    pub(crate) fn from_enable_bits(reg: u8) -> Self {
+        Interrupts {
+            alarm: reg & CTRL2_AIE != 0,
+            timer: reg & CTRL2_TIE != 0,
+        }
+    }
+
+    pub(crate) fn from_flag_bits(reg: u8) -> Self {
+        Interrupts {
+            alarm: reg & CTRL2_AF != 0,
+            timer: reg & CTRL2_TF != 0,
+        }
+    }
+}
+
+/// Control/status 2 register.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Control2 {
+    /// Interrupts enabled on the INT pin.
+    pub enabled: Interrupts,
+    /// Pending flags.
+    pub flags: Interrupts,
+    /// Timer interrupt mode.
+    pub mode: InterruptMode,
+}
+
+impl Control2 {
+    pub(crate) fn from_register(reg: u8) -> Self {
+        Control2 {
+            enabled: Interrupts::from_enable_bits(reg),
+            flags: Interrupts::from_flag_bits(reg),
+            mode: if reg & CTRL2_TI_TP != 0 {
+                InterruptMode::Pulse
+            } else {
+                InterruptMode::Level
+            },
+        }
+    }
+}
+
+/// Control/status 2 mode bits to set and clear for an interrupt mode.
+pub(crate) fn interrupt_mode_bits(mode: InterruptMode) -> (u8, u8) {
+    match mode {
+        InterruptMode::Level => (0, CTRL2_TI_TP),
+        InterruptMode::Pulse => (CTRL2_TI_TP, 0),
+    }
+}
+
+impl Rtc8563 {
+    /// Get control/status 1.
+    pub fn get_control1<I2C: I2c>(&self, bus: &mut I2C) -> Result<Control1, Error<I2C::Error>> {
+        let reg = self.read_register(bus, CONTROL_STATUS_1_REG)?;
+
+        Ok(Control1::from_register(reg))
+    }
+
+    /// Set control/status 1.
+    pub fn set_control1<I2C: I2c>(
+        &self,
+        bus: &mut I2C,
+        control: &Control1,
+    ) -> Result<(), Error<I2C::Error>> {
+        self.write_register(bus, CONTROL_STATUS_1_REG, control.to_register())
+    }
+
+    /// Stop the clock (STOP bit).
+    ///
+    /// The time registers are frozen until `start_clock()`.
+    pub fn stop_clock<I2C: I2c>(&self, bus: &mut I2C) -> Result<(), Error<I2C::Error>> {
+        let control = self.get_control1(bus)?;
+
+        self.set_control1(
+            bus,
+            &Control1 {
+                stop: true,
+                ..control
+            },
+        )
+    }
+
+    /// Restart the clock.
+    ///
+    /// The divider chain is reset, the next second starts exactly now.
+    pub fn start_clock<I2C: I2c>(&self, bus: &mut I2C) -> Result<(), Error<I2C::Error>> {
+        let control = self.get_control1(bus)?;
+
+        self.set_control1(
+            bus,
+            &Control1 {
+                stop: false,
+                ..control
+            },
+        )
+    }
+
+    /// Query if the clock is running (STOP bit cleared).
+    pub fn is_clock_running<I2C: I2c>(&self, bus: &mut I2C) -> Result<bool, Error<I2C::Error>> {
+        Ok(!self.get_control1(bus)?.stop)
+    }
+
+    /// Get control/status 2.
+    pub fn get_control2<I2C: I2c>(&self, bus: &mut I2C) -> Result<Control2, Error<I2C::Error>> {
+        let reg = self.read_register(bus, CONTROL_STATUS_2_REG)?;
+
+        Ok(Control2::from_register(reg))
+    }
+
+    /// Get the pending interrupt flags.
+    pub fn get_flags<I2C: I2c>(&self, bus: &mut I2C) -> Result<Interrupts, Error<I2C::Error>> {
+        Ok(self.get_control2(bus)?.flags)
+    }
+
+    /// Clear the selected interrupt flags, other flags are left untouched.
+    pub fn clear_flags<I2C: I2c>(
+        &self,
+        bus: &mut I2C,
+        flags: Interrupts,
+    ) -> Result<(), Error<I2C::Error>> {
+        self.update_control2(bus, 0, flags.flag_bits())
+    }
+
+    /// Enable the selected interrupts, other interrupts are left untouched.
+    pub fn enable_interrupts<I2C: I2c>(
+        &self,
+        bus: &mut I2C,
+        interrupts: Interrupts,
+    ) -> Result<(), Error<I2C::Error>> {
+        self.update_control2(bus, interrupts.enable_bits(), 0)
+    }
+
+    /// Disable the selected interrupts, other interrupts are left untouched.
+    pub fn disable_interrupts<I2C: I2c>(
+        &self,
+        bus: &mut I2C,
+        interrupts: Interrupts,
+    ) -> Result<(), Error<I2C::Error>> {
+        self.update_control2(bus, 0, interrupts.enable_bits())
+    }
+
+    /// Set the timer interrupt mode (TI_TP).
+    pub fn set_interrupt_mode<I2C: I2c>(
+        &self,
+        bus: &mut I2C,
+        mode: InterruptMode,
+    ) -> Result<(), Error<I2C::Error>> {
+        let (set, clear) = interrupt_mode_bits(mode);
+
+        self.update_control2(bus, set, clear)
+    }
+}
@@ -0,0 +1,377 @@
+//! Drift compensation
+//!
+//! The BM8563 has no trimming register, so the crystal frequency error
+//! accumulates over time. `DriftCompensation` measures it against a reference
+//! time (NTP, GNSS, phone...) supplied by the application, and corrects the
+//! time in software:
+//!
+//! 1. `start_calibration()` when the RTC is read along with a reference time,
+//!    typically right after setting the RTC.
+//! 2. `finish_calibration()` at least `MIN_CALIBRATION_INTERVAL` later, with
+//!    a new reference time. The longer the interval, the more accurate the
+//!    measurement: readings have a one second resolution, which gives
+//!    ±12 ppm over a day.
+//! 3. Correct each read with `correct()` or `get_compensated_unix_time()`,
+//!    or adjust the RTC periodically with `adjust_drift()`.
+//!
+//! All times are Unix timestamps in seconds. The state can be persisted with
+//! `to_bytes()` and `from_bytes()`.
+use embedded_hal::i2c::I2c;
+
+use crate::{DateTime, Error, Rtc8563};
+
+const PPB: i128 = 1_000_000_000;
+
+/// Shortest interval between the two calibration points, in seconds.
+pub const MIN_CALIBRATION_INTERVAL: i64 = 60 * 60;
+
+/// Largest drift accepted by `finish_calibration()`, in parts per billion.
+///
+/// This is far beyond the tolerance of a watch crystal: a larger value means
+/// the RTC or the reference was changed during the calibration.
+pub const MAX_DRIFT_PPB: i32 = 1_000_000;
+
+/// Drift calibration error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DriftError {
+    /// `finish_calibration()` called without `start_calibration()`.
+    NotStarted,
+    /// Less than `MIN_CALIBRATION_INTERVAL` elapsed since the start.
+    IntervalTooShort,
+    /// Measured drift above `MAX_DRIFT_PPB`.
+    OutOfRange,
+}
+
+/// Time pair read at the same instant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct TimePoint {
+    rtc: i64,
+    reference: i64,
+}
+
+impl TimePoint {
+    fn to_bytes(self) -> [u8; 16] {
+        let mut bytes = [0; 16];
+        bytes[..8].copy_from_slice(&self.rtc.to_le_bytes());
+        bytes[8..].copy_from_slice(&self.reference.to_le_bytes());
+
+        bytes
+    }
+
+    /// Return `None` if a time falls outside the range supported by the chip.
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let mut rtc = [0; 8];
+        let mut reference = [0; 8];
+        rtc.copy_from_slice(&bytes[..8]);
+        reference.copy_from_slice(&bytes[8..16]);
+
+        let point = TimePoint {
+            rtc: i64::from_le_bytes(rtc),
+            reference: i64::from_le_bytes(reference),
+        };
+        let in_range = |timestamp| DateTime::from_unix_timestamp(timestamp).is_some();
+
+        (in_range(point.rtc) && in_range(point.reference)).then_some(point)
+    }
+}
+
+const ANCHOR_PRESENT: u8 = 0x01;
+const START_PRESENT: u8 = 0x02;
+
+/// RTC drift measurement and compensation state.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DriftCompensation {
+    /// Drift in parts per billion, positive when the RTC runs fast.
+    drift_ppb: i32,
+    /// Last point the correction is computed from.
+    anchor: Option<TimePoint>,
+    /// Pending calibration start.
+    start: Option<TimePoint>,
+}
+
+impl DriftCompensation {
+    /// Size of the serialized state.
+    pub const BYTES: usize = 37;
+
+    /// Build an uncalibrated state: no correction is applied.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Build a state from a known drift, in parts per billion.
+    ///
+    /// The correction starts from the next `synchronize()` call. Return
+    /// `DriftError::OutOfRange` if the drift is above `MAX_DRIFT_PPB`.
+    pub fn from_drift_ppb(drift_ppb: i32) -> Result<Self, DriftError> {
+        if drift_ppb.unsigned_abs() > MAX_DRIFT_PPB as u32 {
+            return Err(DriftError::OutOfRange);
+        }
+
+        Ok(DriftCompensation {
+            drift_ppb,
+            ..Self::default()
+        })
+    }
+
+    /// Measured drift in parts per billion, positive when the RTC runs fast.
+    pub fn drift_ppb(&self) -> i32 {
+        self.drift_ppb
+    }
+
+    /// Measured drift in parts per million, positive when the RTC runs fast.
+    pub fn drift_ppm(&self) -> f32 {
+        self.drift_ppb as f32 / 1000.0
+    }
+
+    /// Query if a calibration is in progress.
+    pub fn is_calibrating(&self) -> bool {
+        self.start.is_some()
+    }
+
+    /// Record the first calibration point.
+    ///
+    /// This also synchronizes the correction on this point.
+    pub fn start_calibration(&mut self, rtc: i64, reference: i64) {
+        let point = TimePoint { rtc, reference };
+        self.start = Some(point);
+        self.anchor = Some(point);
+    }
+
+    /// Record the second calibration point and compute the drift.
+    ///
+    /// Return the drift in parts per billion. On success, the correction is
+    /// synchronized on this point.
+    pub fn finish_calibration(&mut self, rtc: i64, reference: i64) -> Result<i32, DriftError> {
+        let start = self.start.ok_or(DriftError::NotStarted)?;
+
+        let reference_elapsed = reference as i128 - start.reference as i128;
+        if reference_elapsed < MIN_CALIBRATION_INTERVAL as i128 {
+            return Err(DriftError::IntervalTooShort);
+        }
+        let rtc_elapsed = rtc as i128 - start.rtc as i128;
+
+        let drift_ppb = (rtc_elapsed - reference_elapsed) * PPB / reference_elapsed;
+        if drift_ppb.abs() > MAX_DRIFT_PPB as i128 {
+            return Err(DriftError::OutOfRange);
+        }
+
+        self.drift_ppb = drift_ppb as i32;
+        self.start = None;
+        self.anchor = Some(TimePoint { rtc, reference });
+
+        Ok(self.drift_ppb)
+    }
+
+    /// Restart the correction from a point where the reference time is known,
+    /// e.g. after setting the RTC. The measured drift is kept.
+    pub fn synchronize(&mut self, rtc: i64, reference: i64) {
+        self.anchor = Some(TimePoint { rtc, reference });
+    }
+
+    /// Estimate the reference time from a RTC time.
+    ///
+    /// The time is returned unchanged until synchronized. A result beyond the
+    /// `i64` range saturates.
+    pub fn correct(&self, rtc: i64) -> i64 {
+        let Some(anchor) = self.anchor else {
+            return rtc;
+        };
+
+        let elapsed = rtc as i128 - anchor.rtc as i128;
+        let rate = PPB + self.drift_ppb as i128;
+        // Rounded to the nearest second
+        let corrected = (2 * elapsed * PPB + rate).div_euclid(2 * rate);
+
+        (anchor.reference as i128 + corrected).clamp(i64::MIN as i128, i64::MAX as i128) as i64
+    }
+
+    /// Estimate the reference date and time from a RTC date and time.
+    ///
+    /// Return `None` if the result falls outside the range supported by the
+    /// chip.
+    pub fn correct_datetime(&self, rtc: &DateTime) -> Option<DateTime> {
+        DateTime::from_unix_timestamp(self.correct(rtc.to_unix_timestamp()))
+    }
+
+    /// Serialize the state.
+    pub fn to_bytes(&self) -> [u8; Self::BYTES] {
+        let mut bytes = [0; Self::BYTES];
+
+        bytes[1..5].copy_from_slice(&self.drift_ppb.to_le_bytes());
+        if let Some(anchor) = self.anchor {
+            bytes[0] |= ANCHOR_PRESENT;
+            bytes[5..21].copy_from_slice(&anchor.to_bytes());
+        }
+        if let Some(start) = self.start {
+            bytes[0] |= START_PRESENT;
+            bytes[21..37].copy_from_slice(&start.to_bytes());
+        }
+
+        bytes
+    }
+
+    /// Deserialize a state written by `to_bytes()`.
+    ///
+    /// Return `None` if the data is not a valid state, including times outside
+    /// the range supported by the chip.
+    pub fn from_bytes(bytes: &[u8; Self::BYTES]) -> Option<Self> {
+        if bytes[0] & !(ANCHOR_PRESENT | START_PRESENT) != 0 {
+            return None;
+        }
+
+        let mut drift_ppb = [0; 4];
+        drift_ppb.copy_from_slice(&bytes[1..5]);
+        let drift_ppb = i32::from_le_bytes(drift_ppb);
+        if drift_ppb.unsigned_abs() > MAX_DRIFT_PPB as u32 {
+            return None;
+        }
+
+        let anchor = match bytes[0] & ANCHOR_PRESENT {
+            0 => None,
+            _ => Some(TimePoint::from_bytes(&bytes[5..21])?),
+        };
+        let start = match bytes[0] & START_PRESENT {
+            0 => None,
+            _ => Some(TimePoint::from_bytes(&bytes[21..37])?),
+        };
+
+        Some(DriftCompensation {
+            drift_ppb,
+            anchor,
+            start,
+        })
+    }
+}
+
+impl Rtc8563 {
+    /// Get the date and time as seconds since the Unix epoch, corrected for
+    /// the drift.
+    pub fn get_compensated_unix_time<I2C: I2c>(
+        &self,
+        bus: &mut I2C,
+        compensation: &DriftCompensation,
+    ) -> Result<i64, Error<I2C::Error>> {
+        Ok(compensation.correct(self.get_unix_time(bus)?))
+    }
+
+    /// Write the drift corrected time to the RTC, if it is off by a second or
+    /// more, and synchronize the compensation on it.
+    ///
+    /// Return the adjustment in seconds. As writing the time restarts the
+    /// seconds, this should only be called from time to time, e.g. once a day.
+    /// The updated state should be persisted afterward.
+    pub fn adjust_drift<I2C: I2c>(
+        &self,
+        bus: &mut I2C,
+        compensation: &mut DriftCompensation,
+    ) -> Result<i64, Error<I2C::Error>> {
+        let rtc = self.get_unix_time(bus)?;
+        let corrected = compensation.correct(rtc);
+
+        if corrected != rtc {
+            self.set_unix_time(bus, corrected)?;
+            compensation.synchronize(corrected, corrected);
+        }
+
+        Ok(corrected - rtc)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: i64 = 24 * 60 * 60;
+    const T0: i64 = 1_700_000_000;
+
+    #[test]
+    fn calibration() {
+        let mut compensation = DriftCompensation::new();
+        assert_eq!(compensation.correct(T0), T0);
+        assert_eq!(
+            compensation.finish_calibration(T0, T0),
+            Err(DriftError::NotStarted)
+        );
+
+        compensation.start_calibration(T0, T0);
+        assert_eq!(
+            compensation.finish_calibration(T0 + 60, T0 + 60),
+            Err(DriftError::IntervalTooShort)
+        );
+
+        // One second fast per day
+        assert_eq!(
+            compensation.finish_calibration(T0 + DAY + 1, T0 + DAY),
+            Ok(11_574)
+        );
+        assert!(!compensation.is_calibrating());
+        assert_eq!(compensation.correct(T0 + DAY + 1), T0 + DAY);
+        assert_eq!(compensation.correct(T0 + 2 * DAY + 2), T0 + 2 * DAY);
+
+        let bytes = compensation.to_bytes();
+        assert_eq!(DriftCompensation::from_bytes(&bytes), Some(compensation));
+    }
+
+    #[test]
+    fn drift_out_of_range() {
+        assert!(DriftCompensation::from_drift_ppb(MAX_DRIFT_PPB).is_ok());
+        assert!(DriftCompensation::from_drift_ppb(-MAX_DRIFT_PPB).is_ok());
+        assert_eq!(
+            DriftCompensation::from_drift_ppb(MAX_DRIFT_PPB + 1),
+            Err(DriftError::OutOfRange)
+        );
+        assert_eq!(
+            DriftCompensation::from_drift_ppb(i32::MIN),
+            Err(DriftError::OutOfRange)
+        );
+
+        let mut compensation = DriftCompensation::new();
+        compensation.start_calibration(T0, T0);
+        assert_eq!(
+            compensation.finish_calibration(T0 + 2 * DAY, T0 + DAY),
+            Err(DriftError::OutOfRange)
+        );
+    }
+
+    #[test]
+    fn extreme_times() {
+        let mut compensation = DriftCompensation::new();
+        compensation.start_calibration(i64::MIN, i64::MIN);
+        assert_eq!(
+            compensation.finish_calibration(i64::MAX, i64::MIN),
+            Err(DriftError::IntervalTooShort)
+        );
+        assert_eq!(compensation.finish_calibration(i64::MAX, i64::MAX), Ok(0));
+
+        let mut compensation = DriftCompensation::from_drift_ppb(-MAX_DRIFT_PPB).unwrap();
+        compensation.synchronize(i64::MIN, i64::MAX);
+        assert_eq!(compensation.correct(i64::MAX), i64::MAX);
+        compensation.synchronize(i64::MAX, i64::MIN);
+        assert_eq!(compensation.correct(i64::MIN), i64::MIN);
+    }
+
+    #[test]
+    fn invalid_bytes() {
+        let mut compensation = DriftCompensation::from_drift_ppb(1000).unwrap();
+        compensation.synchronize(T0, T0);
+        let bytes = compensation.to_bytes();
+        assert_eq!(DriftCompensation::from_bytes(&bytes), Some(compensation));
+
+        let mut invalid = bytes;
+        invalid[0] |= 0x80;
+        assert_eq!(DriftCompensation::from_bytes(&invalid), None);
+
+        let mut invalid = bytes;
+        invalid[1..5].copy_from_slice(&i32::MIN.to_le_bytes());
+        assert_eq!(DriftCompensation::from_bytes(&invalid), None);
+
+        let mut invalid = bytes;
+        invalid[5..13].copy_from_slice(&i64::MIN.to_le_bytes());
+        assert_eq!(DriftCompensation::from_bytes(&invalid), None);
+
+        let mut invalid = bytes;
+        invalid[0] |= START_PRESENT;
+        invalid[29..37].copy_from_slice(&i64::MAX.to_le_bytes());
+        assert_eq!(DriftCompensation::from_bytes(&invalid), None);
+    }
+}
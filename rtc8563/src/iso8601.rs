@@ -0,0 +1,228 @@
+//! ISO 8601 formatting and parsing
+//!
+//! `Time`, `Date` and `DateTime` are displayed in the ISO 8601 extended
+//! format (`14:05:09`, `2024-03-31`, `2024-03-31T14:05:09`) and parsed back
+//! with `str::parse()`, without allocation.
+//!
+//! Parsing accepts:
+//!  - Times as `hh:mm:ss` or `hh:mm`. Fractional seconds (`hh:mm:ss.sss`) are
+//!    truncated.
+//!  - Dates as `YYYY-MM-DD`. The weekday is computed.
+//!  - Date and times joined by `T` or a space, optionally followed by `Z`.
+//!    Other UTC offsets are rejected, see `TimeZone` to convert local times.
+use core::fmt;
+use core::str::FromStr;
+
+use crate::{Date, DateTime, Time};
+
+/// Error returned when parsing an ISO 8601 string fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseError {
+    /// The string does not follow the expected format.
+    InvalidFormat,
+    /// A field is out of range, or the date does not exist.
+    OutOfRange,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::InvalidFormat => f.write_str("invalid ISO 8601 format"),
+            ParseError::OutOfRange => f.write_str("date or time out of range"),
+        }
+    }
+}
+
+impl fmt::Display for Time {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:02}:{:02}:{:02}",
+            self.hours, self.minutes, self.seconds
+        )
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+impl fmt::Display for DateTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}T{}", self.date, self.time)
+    }
+}
+
+fn is_digits(s: &str) -> bool {
+    !s.is_empty() && s.bytes().all(|c| c.is_ascii_digit())
+}
+
+/// Parse a fixed number of decimal digits.
+fn digits(s: &str) -> Result<u16, ParseError> {
+    if !is_digits(s) {
+        return Err(ParseError::InvalidFormat);
+    }
+
+    s.parse().map_err(|_| ParseError::InvalidFormat)
+}
+
+/// Split `s` at `len`, checking the separator that follows if any.
+fn field(s: &str, len: usize, separator: Option<u8>) -> Result<(u16, &str), ParseError> {
+    if s.len() < len || !s.is_char_boundary(len) {
+        return Err(ParseError::InvalidFormat);
+    }
+    let (value, rest) = s.split_at(len);
+    let value = digits(value)?;
+
+    match separator {
+        Some(separator) => match rest.as_bytes().first() {
+            Some(&c) if c == separator => Ok((value, &rest[1..])),
+            _ => Err(ParseError::InvalidFormat),
+        },
+        None => Ok((value, rest)),
+    }
+}
+
+impl FromStr for Time {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (hours, rest) = field(s, 2, Some(b':'))?;
+        let (minutes, rest) = field(rest, 2, None)?;
+
+        let seconds = if rest.is_empty() {
+            0
+        } else {
+            let rest = rest.strip_prefix(':').ok_or(ParseError::InvalidFormat)?;
+            let (seconds, rest) = field(rest, 2, None)?;
+            let valid_rest = match rest.strip_prefix(['.', ',']) {
+                Some(fraction) => is_digits(fraction),
+                None => rest.is_empty(),
+            };
+            if !valid_rest {
+                return Err(ParseError::InvalidFormat);
+            }
+            seconds
+        };
+
+        let time = Time {
+            hours: hours as u8,
+            minutes: minutes as u8,
+            seconds: seconds as u8,
+        };
+
+        time.is_valid()
+            .then_some(time)
+            .ok_or(ParseError::OutOfRange)
+    }
+}
+
+impl FromStr for Date {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (year, rest) = field(s, 4, Some(b'-'))?;
+        let (month, rest) = field(rest, 2, Some(b'-'))?;
+        let (day, rest) = field(rest, 2, None)?;
+        if !rest.is_empty() {
+            return Err(ParseError::InvalidFormat);
+        }
+
+        Date::new(year as i16, month as u8, day as u8).ok_or(ParseError::OutOfRange)
+    }
+}
+
+impl FromStr for DateTime {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.strip_suffix('Z').unwrap_or(s);
+        let (date, time) = s.split_once(['T', ' ']).ok_or(ParseError::InvalidFormat)?;
+
+        Ok(DateTime {
+            date: date.parse()?,
+            time: time.parse()?,
+        })
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Time {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "{=u8:02}:{=u8:02}:{=u8:02}",
+            self.hours,
+            self.minutes,
+            self.seconds
+        )
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Date {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "{=i16:04}-{=u8:02}-{=u8:02}",
+            self.year,
+            self.month,
+            self.day
+        )
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for DateTime {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "{}T{}", self.date, self.time)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use std::string::ToString;
+
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let datetime = DateTime {
+            date: Date::new(2024, 3, 9).unwrap(),
+            time: Time {
+                hours: 7,
+                minutes: 5,
+                seconds: 0,
+            },
+        };
+        assert_eq!(datetime.to_string(), "2024-03-09T07:05:00");
+        assert_eq!("2024-03-09T07:05:00".parse(), Ok(datetime));
+
+        assert_eq!("2024-03-09 07:05".parse(), Ok(datetime));
+        assert_eq!("2024-03-09T07:05:00Z".parse(), Ok(datetime));
+        assert_eq!("2024-03-09T07:05:00.250Z".parse(), Ok(datetime));
+        assert_eq!("2024-03-09T07:05:00,5".parse(), Ok(datetime));
+    }
+
+    #[test]
+    fn invalid() {
+        for (s, error) in [
+            ("", ParseError::InvalidFormat),
+            ("2024-03-09", ParseError::InvalidFormat),
+            ("2024-3-09T07:05:00", ParseError::InvalidFormat),
+            ("2024-03-09T07:05:", ParseError::InvalidFormat),
+            ("2024-03-09T07:05:00.", ParseError::InvalidFormat),
+            ("2024-03-09T07:05:00+01:00", ParseError::InvalidFormat),
+            ("+024-03-09T07:05:00", ParseError::InvalidFormat),
+            ("2024-02-30T07:05:00", ParseError::OutOfRange),
+            ("2024-03-09T24:00:00", ParseError::OutOfRange),
+            ("1899-12-31T23:59:59", ParseError::OutOfRange),
+        ] {
+            assert_eq!(s.parse::<DateTime>(), Err(error), "{s}");
+        }
+    }
+}
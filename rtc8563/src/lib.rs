@@ -4,8 +4,44 @@
 #![no_std]
 use embedded_hal::i2c::I2c;
 
+use calendar::days_in_month;
+
+mod alarm;
+#[cfg(feature = "async")]
+mod asynch;
+mod calendar;
+#[cfg(feature = "chrono")]
+mod chrono_support;
+mod clkout;
+mod control;
+mod drift;
+mod iso8601;
+#[cfg(feature = "rtcc")]
+mod rtcc_support;
+#[cfg(feature = "sim")]
+pub mod sim;
+#[cfg(feature = "time")]
+mod time_support;
+mod timer;
+mod tz;
+mod unix;
+
+pub use alarm::Alarm;
+#[cfg(feature = "async")]
+pub use asynch::Rtc8563Async;
+pub use clkout::{ClkOut, SecondTick};
+pub use control::{Control1, Control2, InterruptMode, Interrupts};
+pub use drift::{DriftCompensation, DriftError, MAX_DRIFT_PPB, MIN_CALIBRATION_INTERVAL};
+pub use iso8601::ParseError;
+#[cfg(feature = "rtcc")]
+pub use rtcc_support::Rtc8563Device;
+pub use timer::{TimerConfig, TimerError, TimerSource};
+pub use tz::{TimeZone, TzParseError, UtcOffset};
+
 pub const RTC8563_DEFAULT_I2C_ADDRESS: u8 = 0x51;
 
+const CONTROL_STATUS_1_REG: u8 = 0x00;
+const CONTROL_STATUS_2_REG: u8 = 0x01;
 const SECONDS_REG: u8 = 0x02;
 //const MINI2CES_REG: u8 = 0x03;
 //const HOURS_REG: u8 = 0x04;
@@ -13,14 +49,70 @@ const DAYS_REG: u8 = 0x05;
 //const WEEKDAY_REG: u8 = 0x06;
 //const MONI2CHS_REG: u8 = 0x07;
 //const YEAR_REG: u8 = 0x08;
+const MINUTE_ALARM_REG: u8 = 0x09;
+//const HOUR_ALARM_REG: u8 = 0x0A;
+//const DAY_ALARM_REG: u8 = 0x0B;
+//const WEEKDAY_ALARM_REG: u8 = 0x0C;
+const CLKOUT_CONTROL_REG: u8 = 0x0D;
+const TIMER_CONTROL_REG: u8 = 0x0E;
+const TIMER_REG: u8 = 0x0F;
 
-#[derive(Debug, Clone, Copy, Default)]
+/// Voltage-low flag, bit 7 of the seconds register.
+const SECONDS_VL: u8 = 0x80;
+
+// Control/status 1 bits
+const CTRL1_TEST1: u8 = 0x80;
+const CTRL1_STOP: u8 = 0x20;
+const CTRL1_TESTC: u8 = 0x08;
+
+// Control/status 2 bits
+const CTRL2_TIE: u8 = 0x01;
+const CTRL2_AIE: u8 = 0x02;
+const CTRL2_TF: u8 = 0x04;
+const CTRL2_AF: u8 = 0x08;
+const CTRL2_TI_TP: u8 = 0x10;
+
+/// RTC driver error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error<E> {
+    /// I2C bus error.
+    I2c(E),
+    /// Time to write is out of range.
+    InvalidTime,
+    /// Date to write is out of range, or does not exist (e.g. 31 February).
+    InvalidDate,
+    /// Year to write is outside the range of the century policy, see
+    /// `CenturyPolicy`.
+    YearOutOfRange,
+    /// Alarm to write is out of range.
+    InvalidAlarm,
+    /// Registers read from the chip do not decode to a valid BCD value in
+    /// range.
+    InvalidRegister,
+}
+
+impl<E> From<E> for Error<E> {
+    fn from(error: E) -> Self {
+        Error::I2c(error)
+    }
+}
+
+/// Error returned when converting from or to another crate date and time
+/// types fails, because the value does not exist or is out of range.
+#[cfg(any(feature = "chrono", feature = "time"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConversionError;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Time {
     pub hours: u8,
     pub minutes: u8,
     pub seconds: u8,
 }
 
+/// Calendar date.
+///
+/// Dates compare on year, month and day only, the weekday is ignored.
 #[derive(Debug, Clone, Copy, Default)]
 pub struct Date {
     pub week_day: u8,
@@ -29,17 +121,98 @@ pub struct Date {
     pub year: i16,
 }
 
+/// Date and time, read or written in a single transfer.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct DateTime {
+    pub date: Date,
+    pub time: Time,
+}
+
+/// Clock integrity, as reported by the voltage-low (VL) flag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClockIntegrity {
+    /// The clock has been running since the time was last set.
+    Guaranteed,
+    /// The backup supply dropped too low and the clock may have stopped or
+    /// been corrupted. The time is not trustworthy until set again.
+    Lost,
+}
+
+/// Interpretation of the century bit (C, bit 7 of the months register).
+///
+/// The chip only stores a two digits year. The century bit is toggled when
+/// the year wraps from 99 to 00, its meaning is up to the software. The chip
+/// considers year 00 a leap year, so 1900 and 2100 are not tracked correctly
+/// past February.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CenturyPolicy {
+    /// C set: 1900-1999, C cleared: 2000-2099.
+    #[default]
+    Set1900,
+    /// C cleared: 2000-2099, C set: 2100-2199.
+    ///
+    /// The chip counts a 29 February 2100, which does not exist: reading the
+    /// date on that day returns `Error::InvalidRegister`.
+    Set2100,
+}
+
+impl CenturyPolicy {
+    /// First year of the supported range.
+    pub fn first_year(self) -> i16 {
+        match self {
+            CenturyPolicy::Set1900 => 1900,
+            CenturyPolicy::Set2100 => 2000,
+        }
+    }
+
+    /// Last year of the supported range.
+    pub fn last_year(self) -> i16 {
+        self.first_year() + 199
+    }
+
+    /// Query if a year is in the supported range.
+    pub fn contains(self, year: i16) -> bool {
+        (self.first_year()..=self.last_year()).contains(&year)
+    }
+
+    /// Year from the century bit and the two digits year.
+    fn decode_year(self, century: bool, year: u8) -> i16 {
+        let base = match (self, century) {
+            (CenturyPolicy::Set1900, true) => 1900,
+            (CenturyPolicy::Set1900, false) | (CenturyPolicy::Set2100, false) => 2000,
+            (CenturyPolicy::Set2100, true) => 2100,
+        };
+
+        base + year as i16
+    }
+
+    /// Century bit for a year in the supported range.
+    fn century_bit(self, year: i16) -> bool {
+        match self {
+            CenturyPolicy::Set1900 => year < 2000,
+            CenturyPolicy::Set2100 => year >= 2100,
+        }
+    }
+}
+
 pub struct Rtc8563 {
     address: u8,
+    auto_week_day: bool,
+    century: CenturyPolicy,
 }
 
 pub type Bcd = u8;
 
-fn bcd2byte(bdc: Bcd) -> u8 {
+/// Decode a BCD value, return `None` if a digit is above 9.
+fn bcd2byte(bdc: Bcd) -> Option<u8> {
     let dec = (bdc & 0xF0) >> 4;
     let unit = bdc & 0x0F;
 
-    (10 * dec) + unit
+    if dec > 9 || unit > 9 {
+        return None;
+    }
+
+    Some((10 * dec) + unit)
 }
 
 fn byte2bcd(value: u8) -> Bcd {
@@ -54,89 +227,402 @@ fn byte2bcd(value: u8) -> Bcd {
     (bcd_high << 4) + bcd_low
 }
 
+impl Time {
+    /// Check that all fields are in range.
+    pub fn is_valid(&self) -> bool {
+        self.hours < 24 && self.minutes < 60 && self.seconds < 60
+    }
+
+    /// Decode from the time registers (0x02-0x04).
+    pub(crate) fn from_registers(regs: &[u8; 3]) -> Option<Self> {
+        let time = Time {
+            hours: bcd2byte(regs[2] & 0x3F)?,
+            minutes: bcd2byte(regs[1] & 0x7F)?,
+            seconds: bcd2byte(regs[0] & 0x7F)?,
+        };
+
+        time.is_valid().then_some(time)
+    }
+
+    /// Encode to the time registers (0x02-0x04), with the VL flag cleared.
+    ///
+    /// The time must be valid.
+    pub(crate) fn to_registers(self) -> [u8; 3] {
+        [
+            byte2bcd(self.seconds) & !SECONDS_VL,
+            byte2bcd(self.minutes),
+            byte2bcd(self.hours),
+        ]
+    }
+}
+
+impl Date {
+    /// Check that all fields are in range and that the date exists.
+    ///
+    /// The year must be in the range supported by the chip with any century
+    /// policy (1900-2199). See `CenturyPolicy` for the range of a given
+    /// policy.
+    pub fn is_valid(&self) -> bool {
+        (1900..=2199).contains(&self.year)
+            && (1..=days_in_month(self.year, self.month)).contains(&self.day)
+            && self.week_day < 7
+    }
+
+    /// Decode from the date registers (0x05-0x08).
+    pub(crate) fn from_registers(regs: &[u8; 4], century: CenturyPolicy) -> Option<Self> {
+        let date = Date {
+            day: bcd2byte(regs[0] & 0x3f)?,
+            week_day: bcd2byte(regs[1] & 0x07)?,
+            month: bcd2byte(regs[2] & 0x1f)?,
+            year: century.decode_year(regs[2] & 0x80 == 0x80, bcd2byte(regs[3])?),
+        };
+
+        date.is_valid().then_some(date)
+    }
+
+    /// Encode to the date registers (0x05-0x08).
+    ///
+    /// The date must be valid, and the year in the century policy range.
+    pub(crate) fn to_registers(self, century: CenturyPolicy) -> [u8; 4] {
+        let mut regs: [u8; 4] = [0; 4];
+        regs[0] = byte2bcd(self.day) & 0x3f;
+        regs[1] = byte2bcd(self.week_day) & 0x07;
+        regs[2] = byte2bcd(self.month) & 0x1f;
+
+        if century.century_bit(self.year) {
+            regs[2] |= 0x80;
+        }
+        regs[3] = byte2bcd((self.year % 100) as u8);
+
+        regs
+    }
+}
+
+impl DateTime {
+    /// Decode from the time and date registers (0x02-0x08).
+    pub(crate) fn from_registers(regs: &[u8; 7], century: CenturyPolicy) -> Option<Self> {
+        Some(DateTime {
+            time: Time::from_registers(&[regs[0], regs[1], regs[2]])?,
+            date: Date::from_registers(&[regs[3], regs[4], regs[5], regs[6]], century)?,
+        })
+    }
+
+    /// Encode to the time and date registers (0x02-0x08).
+    ///
+    /// The date and time must be valid, and the year in the century policy
+    /// range.
+    pub(crate) fn to_registers(self, century: CenturyPolicy) -> [u8; 7] {
+        let time = self.time.to_registers();
+        let date = self.date.to_registers(century);
+
+        [
+            time[0], time[1], time[2], date[0], date[1], date[2], date[3],
+        ]
+    }
+}
+
+fn integrity_from_seconds(seconds: u8) -> ClockIntegrity {
+    if seconds & SECONDS_VL == SECONDS_VL {
+        ClockIntegrity::Lost
+    } else {
+        ClockIntegrity::Guaranteed
+    }
+}
+
+/// Return the decoded value if the clock integrity is guaranteed.
+fn decode_if_valid<T, E>(seconds: u8, value: Option<T>) -> Result<Option<T>, Error<E>> {
+    match integrity_from_seconds(seconds) {
+        ClockIntegrity::Guaranteed => value.map(Some).ok_or(Error::InvalidRegister),
+        ClockIntegrity::Lost => Ok(None),
+    }
+}
+
+/// Validate and encode a time write transfer.
+fn time_write_buffer<E>(time: &Time) -> Result<[u8; 4], Error<E>> {
+    if !time.is_valid() {
+        return Err(Error::InvalidTime);
+    }
+
+    let regs = time.to_registers();
+
+    Ok([SECONDS_REG, regs[0], regs[1], regs[2]])
+}
+
+/// New control/status 2 value, with the `set` bits set and `clear` bits
+/// cleared.
+///
+/// The AF and TF flags can only be cleared: writing a 1 leaves them
+/// unchanged. They are therefore written as 1 unless listed in `clear`, so
+/// a flag raised between the read and the write is not lost.
+fn update_control2(ctrl2: u8, set: u8, clear: u8) -> u8 {
+    (((ctrl2 | CTRL2_AF | CTRL2_TF) & !clear) | set) & 0x1F
+}
+
 impl Rtc8563 {
     pub fn new(address: u8) -> Self {
-        Self { address }
+        Self {
+            address,
+            auto_week_day: false,
+            century: CenturyPolicy::default(),
+        }
+    }
+
+    /// Set the interpretation of the century bit.
+    ///
+    /// Dates read or written outside the policy range fail, see
+    /// `CenturyPolicy`.
+    pub fn with_century_policy(mut self, century: CenturyPolicy) -> Self {
+        self.century = century;
+        self
+    }
+
+    /// Compute the weekday when writing a date.
+    ///
+    /// When enabled, `set_date()` and `set_datetime()` ignore the `week_day`
+    /// field and write the weekday computed from the calendar.
+    pub fn with_auto_week_day(mut self, enable: bool) -> Self {
+        self.auto_week_day = enable;
+        self
+    }
+
+    /// Date to write, with the weekday computed if enabled.
+    fn date_to_write(&self, date: &Date) -> Date {
+        if self.auto_week_day {
+            date.with_weekday()
+        } else {
+            *date
+        }
+    }
+
+    /// Validate and encode a date write transfer.
+    fn date_write_buffer<E>(&self, date: &Date) -> Result<[u8; 5], Error<E>> {
+        let date = self.date_to_write(date);
+        if !date.is_valid() {
+            return Err(Error::InvalidDate);
+        }
+        if !self.century.contains(date.year) {
+            return Err(Error::YearOutOfRange);
+        }
+
+        let regs = date.to_registers(self.century);
+
+        Ok([DAYS_REG, regs[0], regs[1], regs[2], regs[3]])
+    }
+
+    /// Validate and encode a date and time write transfer.
+    fn datetime_write_buffer<E>(&self, datetime: &DateTime) -> Result<[u8; 8], Error<E>> {
+        let datetime = DateTime {
+            date: self.date_to_write(&datetime.date),
+            time: datetime.time,
+        };
+        if !datetime.time.is_valid() {
+            return Err(Error::InvalidTime);
+        }
+        if !datetime.date.is_valid() {
+            return Err(Error::InvalidDate);
+        }
+        if !self.century.contains(datetime.date.year) {
+            return Err(Error::YearOutOfRange);
+        }
+
+        let regs = datetime.to_registers(self.century);
+        let mut buffer: [u8; 8] = [SECONDS_REG, 0, 0, 0, 0, 0, 0, 0];
+        buffer[1..].copy_from_slice(&regs);
+
+        Ok(buffer)
     }
 
     /// Initialize chip.
     ///
-    /// I2Chis clear Control/status 1 and 2.
-    pub fn init<I2C: I2c>(&self, bus: &mut I2C) -> Result<(), I2C::Error> {
-        let buffer: [u8; 3] = [0, 0, 0];
-        bus.write(self.address, &buffer)
+    /// This clears control/status 1, restarting the clock if it was stopped.
+    /// Control/status 2 is kept: the interrupt enables and the alarm and timer
+    /// flags survive a CPU reset, so `alarm_fired()` still tells if the chip
+    /// woke the system up.
+    pub fn init<I2C: I2c>(&self, bus: &mut I2C) -> Result<(), Error<I2C::Error>> {
+        self.set_control1(bus, &Control1::default())
     }
 
-    pub fn get_time<I2C: I2c>(&self, bus: &mut I2C) -> Result<Time, I2C::Error> {
+    /// Get the time.
+    ///
+    /// The clock integrity is not checked, see `get_valid_time()`.
+    pub fn get_time<I2C: I2c>(&self, bus: &mut I2C) -> Result<Time, Error<I2C::Error>> {
+        let buffer = self.read_time_registers(bus)?;
+
+        Time::from_registers(&buffer).ok_or(Error::InvalidRegister)
+    }
+
+    /// Get the time, if the clock integrity is guaranteed.
+    ///
+    /// Return `None` if the voltage-low flag is set, i.e. the time must be set
+    /// again before it can be trusted.
+    pub fn get_valid_time<I2C: I2c>(
+        &self,
+        bus: &mut I2C,
+    ) -> Result<Option<Time>, Error<I2C::Error>> {
+        let buffer = self.read_time_registers(bus)?;
+
+        decode_if_valid(buffer[0], Time::from_registers(&buffer))
+    }
+
+    /// Query the clock integrity (VL flag).
+    ///
+    /// The flag is cleared when the time is set with `set_time()`.
+    pub fn clock_integrity<I2C: I2c>(
+        &self,
+        bus: &mut I2C,
+    ) -> Result<ClockIntegrity, Error<I2C::Error>> {
+        let seconds = self.read_register(bus, SECONDS_REG)?;
+
+        Ok(integrity_from_seconds(seconds))
+    }
+
+    fn read_time_registers<I2C: I2c>(&self, bus: &mut I2C) -> Result<[u8; 3], Error<I2C::Error>> {
         let addr_buffer: [u8; 1] = [SECONDS_REG];
         let mut buffer: [u8; 3] = [0, 0, 0];
 
         bus.write_read(self.address, &addr_buffer, &mut buffer)?;
 
-        Ok(Time {
-            hours: bcd2byte(buffer[2] & 0x3F),
-            minutes: bcd2byte(buffer[1] & 0x7F),
-            seconds: bcd2byte(buffer[0] & 0x7F),
-        })
+        Ok(buffer)
     }
 
-    pub fn set_time<I2C: I2c>(&self, bus: &mut I2C, time: &Time) -> Result<(), I2C::Error> {
-        let buffer: [u8; 4] = [
-            SECONDS_REG,
-            byte2bcd(time.seconds),
-            byte2bcd(time.minutes),
-            byte2bcd(time.hours),
-        ];
+    /// Set the time.
+    ///
+    /// This also clears the voltage-low flag. Return `Error::InvalidTime` if
+    /// a field is out of range.
+    pub fn set_time<I2C: I2c>(&self, bus: &mut I2C, time: &Time) -> Result<(), Error<I2C::Error>> {
+        let buffer = time_write_buffer(time)?;
 
-        bus.write(self.address, &buffer)
+        bus.write(self.address, &buffer)?;
+
+        Ok(())
     }
 
-    pub fn get_date<I2C: I2c>(&self, bus: &mut I2C) -> Result<Date, I2C::Error> {
+    pub fn get_date<I2C: I2c>(&self, bus: &mut I2C) -> Result<Date, Error<I2C::Error>> {
         let addr_buffer: [u8; 1] = [DAYS_REG];
         let mut buffer: [u8; 4] = [0; 4];
 
         bus.write_read(self.address, &addr_buffer, &mut buffer)?;
 
-        Ok(Date {
-            day: bcd2byte(buffer[0] & 0x3f),
-            week_day: bcd2byte(buffer[1] & 0x0f),
-            month: bcd2byte(buffer[2] & 0x1f),
-            year: if buffer[2] & 0x80 == 0x80 {
-                1900 + bcd2byte(buffer[3]) as i16
-            } else {
-                2000 + bcd2byte(buffer[3]) as i16
-            },
-        })
+        Date::from_registers(&buffer, self.century).ok_or(Error::InvalidRegister)
     }
 
-    pub fn set_date<I2C: I2c>(&self, bus: &mut I2C, date: &Date) -> Result<(), I2C::Error> {
-        let mut buffer: [u8; 5] = [DAYS_REG, 0, 0, 0, 0];
-        buffer[1] = byte2bcd(date.day) & 0x3f;
-        buffer[2] = byte2bcd(date.week_day) & 0x0f;
-        buffer[3] = byte2bcd(date.month) & 0x1f;
+    /// Set the date.
+    ///
+    /// Return `Error::InvalidDate` if the date does not exist, or
+    /// `Error::YearOutOfRange` if the year is out of the century policy
+    /// range. See `with_auto_week_day()` to have the weekday computed.
+    pub fn set_date<I2C: I2c>(&self, bus: &mut I2C, date: &Date) -> Result<(), Error<I2C::Error>> {
+        let buffer = self.date_write_buffer(date)?;
 
-        if date.year < 2000 {
-            buffer[3] |= 0x80;
-        }
-        buffer[4] = byte2bcd((date.year % 100) as u8);
+        bus.write(self.address, &buffer)?;
+
+        Ok(())
+    }
+
+    /// Get the date and time.
+    ///
+    /// All registers are read in a single transfer. The chip freezes its
+    /// counters during the transfer, so the date and time are consistent.
+    /// The clock integrity is not checked, see `get_valid_datetime()`.
+    pub fn get_datetime<I2C: I2c>(&self, bus: &mut I2C) -> Result<DateTime, Error<I2C::Error>> {
+        let buffer = self.read_datetime_registers(bus)?;
+
+        DateTime::from_registers(&buffer, self.century).ok_or(Error::InvalidRegister)
+    }
+
+    /// Get the date and time, if the clock integrity is guaranteed.
+    ///
+    /// Return `None` if the voltage-low flag is set.
+    pub fn get_valid_datetime<I2C: I2c>(
+        &self,
+        bus: &mut I2C,
+    ) -> Result<Option<DateTime>, Error<I2C::Error>> {
+        let buffer = self.read_datetime_registers(bus)?;
+
+        decode_if_valid(buffer[0], DateTime::from_registers(&buffer, self.century))
+    }
+
+    fn read_datetime_registers<I2C: I2c>(
+        &self,
+        bus: &mut I2C,
+    ) -> Result<[u8; 7], Error<I2C::Error>> {
+        let addr_buffer: [u8; 1] = [SECONDS_REG];
+        let mut buffer: [u8; 7] = [0; 7];
+
+        bus.write_read(self.address, &addr_buffer, &mut buffer)?;
+
+        Ok(buffer)
+    }
+
+    /// Set the date and time.
+    ///
+    /// The clock is stopped (STOP bit) while the registers are written in a
+    /// single transfer, and restarted afterward. The seconds start counting
+    /// from the written value on restart. This also clears the voltage-low
+    /// flag.
+    pub fn set_datetime<I2C: I2c>(
+        &self,
+        bus: &mut I2C,
+        datetime: &DateTime,
+    ) -> Result<(), Error<I2C::Error>> {
+        let buffer = self.datetime_write_buffer(datetime)?;
+
+        let control = self.get_control1(bus)?;
+        self.set_control1(
+            bus,
+            &Control1 {
+                stop: true,
+                ..control
+            },
+        )?;
+
+        // Always restart the clock, even if the transfer failed.
+        let result = bus.write(self.address, &buffer);
+        self.set_control1(
+            bus,
+            &Control1 {
+                stop: false,
+                ..control
+            },
+        )?;
+
+        Ok(result?)
+    }
+
+    /// Read-modify-write control/status 2, see `update_control2()`.
+    fn update_control2<I2C: I2c>(
+        &self,
+        bus: &mut I2C,
+        set: u8,
+        clear: u8,
+    ) -> Result<(), Error<I2C::Error>> {
+        let ctrl2 = self.read_register(bus, CONTROL_STATUS_2_REG)?;
 
-        bus.write(self.address, &buffer)
+        self.write_register(
+            bus,
+            CONTROL_STATUS_2_REG,
+            update_control2(ctrl2, set, clear),
+        )
     }
 
-    #[allow(dead_code)]
     fn write_register<I2C: I2c>(
         &self,
         bus: &mut I2C,
         reg_addr: u8,
         reg_value: u8,
-    ) -> Result<(), I2C::Error> {
+    ) -> Result<(), Error<I2C::Error>> {
         let buffer: [u8; 2] = [reg_addr, reg_value];
 
-        bus.write(self.address, &buffer)
+        bus.write(self.address, &buffer)?;
+
+        Ok(())
     }
 
-    #[allow(dead_code)]
-    fn read_register<I2C: I2c>(&self, bus: &mut I2C, reg_addr: u8) -> Result<u8, I2C::Error> {
+    fn read_register<I2C: I2c>(
+        &self,
+        bus: &mut I2C,
+        reg_addr: u8,
+    ) -> Result<u8, Error<I2C::Error>> {
         let addr_buffer: [u8; 1] = [reg_addr];
         let mut buffer: [u8; 1] = [0];
 
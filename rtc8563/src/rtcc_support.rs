@@ -0,0 +1,47 @@
+//! `rtcc` trait implementation
+//!
+//! Enabled with the `rtcc` feature.
+use embedded_hal::i2c::I2c;
+use rtcc::{DateTimeAccess, NaiveDateTime};
+
+use crate::{DateTime, Error, Rtc8563};
+
+/// RTC driver bound to its I2C bus.
+///
+/// `Rtc8563` does not own the bus, while the `rtcc` traits do not take it as
+/// argument. This adapter holds both. The bus can either be owned, or borrowed
+/// by passing a `&mut` reference.
+pub struct Rtc8563Device<I2C> {
+    rtc: Rtc8563,
+    bus: I2C,
+}
+
+impl Rtc8563 {
+    /// Bind the driver to an I2C bus, to use it through the `rtcc` traits.
+    pub fn with_bus<I2C: I2c>(self, bus: I2C) -> Rtc8563Device<I2C> {
+        Rtc8563Device { rtc: self, bus }
+    }
+}
+
+impl<I2C> Rtc8563Device<I2C> {
+    /// Release the driver and the I2C bus.
+    pub fn release(self) -> (Rtc8563, I2C) {
+        (self.rtc, self.bus)
+    }
+}
+
+impl<I2C: I2c> DateTimeAccess for Rtc8563Device<I2C> {
+    type Error = Error<I2C::Error>;
+
+    fn datetime(&mut self) -> Result<NaiveDateTime, Self::Error> {
+        let datetime = self.rtc.get_datetime(&mut self.bus)?;
+
+        NaiveDateTime::try_from(datetime).map_err(|_| Error::InvalidRegister)
+    }
+
+    fn set_datetime(&mut self, datetime: &NaiveDateTime) -> Result<(), Self::Error> {
+        let datetime = DateTime::try_from(*datetime).map_err(|_| Error::InvalidDate)?;
+
+        self.rtc.set_datetime(&mut self.bus, &datetime)
+    }
+}
@@ -0,0 +1,338 @@
+//! Simulated BM8563
+//!
+//! `Bm8563Sim` implements `embedded_hal::i2c::I2c` on top of an in-memory
+//! register file, so the driver and application logic can be run on a host,
+//! without the board. Time only passes when `advance()` is called.
+//!
+//! Enabled with the `sim` feature.
+//!
+//! The simulation covers:
+//!  - Register auto-increment, bit masks and flag clearing semantics.
+//!  - BCD time keeping with carries up to the century bit, and the chip
+//!    leap year rule (every year divisible by 4).
+//!  - Voltage-low flag, set on power-up and with `power_loss()`.
+//!  - STOP bit, alarm flag (AF) and countdown timer flag (TF).
+//!
+//! The pulse interrupt mode (TI_TP) is not simulated: `interrupt()` follows
+//! the flags.
+use core::time::Duration;
+
+use embedded_hal::i2c::{ErrorKind, ErrorType, I2c, NoAcknowledgeSource, Operation};
+
+use crate::{
+    CONTROL_STATUS_1_REG, CONTROL_STATUS_2_REG, CTRL1_STOP, CTRL2_AF, CTRL2_AIE, CTRL2_TF,
+    CTRL2_TIE, MINUTE_ALARM_REG, RTC8563_DEFAULT_I2C_ADDRESS, SECONDS_REG, SECONDS_VL,
+    TIMER_CONTROL_REG, TIMER_REG, bcd2byte, byte2bcd,
+};
+
+const REGISTER_COUNT: usize = 16;
+
+/// Writable bits of each register.
+const REGISTER_MASKS: [u8; REGISTER_COUNT] = [
+    0xA8, 0x1F, 0xFF, 0x7F, 0x3F, 0x3F, 0x07, 0x9F, 0xFF, 0xFF, 0xBF, 0xBF, 0x87, 0x83, 0x83, 0xFF,
+];
+
+const MINUTES_REG: u8 = 0x03;
+const HOURS_REG: u8 = 0x04;
+const DAYS_REG: u8 = 0x05;
+const WEEKDAY_REG: u8 = 0x06;
+const MONTHS_REG: u8 = 0x07;
+const YEARS_REG: u8 = 0x08;
+
+/// Century bit of the months register.
+const MONTHS_CENTURY: u8 = 0x80;
+
+/// Timer enable bit (TE) of the timer control register.
+const TIMER_ENABLE: u8 = 0x80;
+
+const NANOS_PER_SECOND: u128 = 1_000_000_000;
+
+/// Simulated I2C error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SimError {
+    /// Transfer to another address than the simulated chip.
+    NoAcknowledge,
+}
+
+impl embedded_hal::i2c::Error for SimError {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            SimError::NoAcknowledge => ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address),
+        }
+    }
+}
+
+/// Simulated BM8563 chip.
+pub struct Bm8563Sim {
+    address: u8,
+    registers: [u8; REGISTER_COUNT],
+    /// Register address pointer, auto-incremented on each byte.
+    pointer: u8,
+    /// Timer value reloaded when the countdown reaches zero.
+    timer_reload: u8,
+    /// Time elapsed since the last second tick, in nanoseconds.
+    clock_elapsed: u128,
+    /// Time elapsed since the last timer tick, in nanoseconds scaled by the
+    /// timer source period denominator.
+    timer_elapsed: u128,
+}
+
+impl Default for Bm8563Sim {
+    fn default() -> Self {
+        Self::new(RTC8563_DEFAULT_I2C_ADDRESS)
+    }
+}
+
+impl Bm8563Sim {
+    /// Build a chip in its power-up state.
+    ///
+    /// The clock is set to 2000-01-01 00:00:00 (Saturday) with the
+    /// voltage-low flag set, as after the first power-up.
+    pub fn new(address: u8) -> Self {
+        let mut registers = [0; REGISTER_COUNT];
+        registers[SECONDS_REG as usize] = SECONDS_VL;
+        registers[DAYS_REG as usize] = 0x01;
+        registers[WEEKDAY_REG as usize] = 0x06;
+        registers[MONTHS_REG as usize] = 0x01;
+        // Alarms disabled
+        registers[MINUTE_ALARM_REG as usize..MINUTE_ALARM_REG as usize + 4].fill(0x80);
+
+        Self {
+            address,
+            registers,
+            pointer: 0,
+            timer_reload: 0,
+            clock_elapsed: 0,
+            timer_elapsed: 0,
+        }
+    }
+
+    /// Raw register file.
+    pub fn registers(&self) -> &[u8; REGISTER_COUNT] {
+        &self.registers
+    }
+
+    /// Simulate a drop of the backup supply: set the voltage-low flag.
+    pub fn power_loss(&mut self) {
+        self.registers[SECONDS_REG as usize] |= SECONDS_VL;
+    }
+
+    /// Query if the INT pin is asserted.
+    ///
+    /// INT is asserted while an enabled flag is set.
+    pub fn interrupt(&self) -> bool {
+        let ctrl2 = self.registers[CONTROL_STATUS_2_REG as usize];
+
+        (ctrl2 & CTRL2_AIE != 0 && ctrl2 & CTRL2_AF != 0)
+            || (ctrl2 & CTRL2_TIE != 0 && ctrl2 & CTRL2_TF != 0)
+    }
+
+    /// Let time pass.
+    ///
+    /// Nothing happens while the clock is stopped (STOP bit set).
+    pub fn advance(&mut self, duration: Duration) {
+        if self.registers[CONTROL_STATUS_1_REG as usize] & CTRL1_STOP != 0 {
+            return;
+        }
+
+        self.clock_elapsed += duration.as_nanos();
+        while self.clock_elapsed >= NANOS_PER_SECOND {
+            self.clock_elapsed -= NANOS_PER_SECOND;
+            self.tick_second();
+        }
+
+        let timer_control = self.registers[TIMER_CONTROL_REG as usize];
+        if timer_control & TIMER_ENABLE != 0 {
+            let (period, denominator) = match timer_control & 0x03 {
+                0x00 => (NANOS_PER_SECOND, 4096),
+                0x01 => (NANOS_PER_SECOND, 64),
+                0x02 => (NANOS_PER_SECOND, 1),
+                _ => (60 * NANOS_PER_SECOND, 1),
+            };
+
+            self.timer_elapsed += duration.as_nanos() * denominator;
+            while self.timer_elapsed >= period {
+                self.timer_elapsed -= period;
+                self.tick_timer();
+            }
+        }
+    }
+
+    fn write_register(&mut self, address: u8, value: u8) {
+        let index = address as usize;
+        let value = value & REGISTER_MASKS[index];
+
+        match address {
+            CONTROL_STATUS_1_REG => {
+                // Releasing STOP resets the divider chain.
+                let stopped = self.registers[index] & CTRL1_STOP != 0;
+                if stopped && value & CTRL1_STOP == 0 {
+                    self.clock_elapsed = 0;
+                }
+                self.registers[index] = value;
+            }
+            CONTROL_STATUS_2_REG => {
+                // Flags can only be cleared.
+                let flags = CTRL2_AF | CTRL2_TF;
+                let kept_flags = self.registers[index] & value & flags;
+                self.registers[index] = (value & !flags) | kept_flags;
+            }
+            TIMER_REG => {
+                self.timer_reload = value;
+                self.registers[index] = value;
+            }
+            _ => self.registers[index] = value,
+        }
+    }
+
+    fn read_bcd(&self, address: u8, mask: u8) -> u8 {
+        bcd2byte(self.registers[address as usize] & mask).unwrap_or(0)
+    }
+
+    fn write_bcd(&mut self, address: u8, mask: u8, value: u8) {
+        let index = address as usize;
+        self.registers[index] = (self.registers[index] & !mask) | byte2bcd(value);
+    }
+
+    fn tick_second(&mut self) {
+        let seconds = self.read_bcd(SECONDS_REG, 0x7F) + 1;
+        if seconds < 60 {
+            self.write_bcd(SECONDS_REG, 0x7F, seconds);
+            return;
+        }
+        self.write_bcd(SECONDS_REG, 0x7F, 0);
+
+        self.tick_minute();
+        self.check_alarm();
+    }
+
+    fn tick_minute(&mut self) {
+        let minutes = self.read_bcd(MINUTES_REG, 0x7F) + 1;
+        if minutes < 60 {
+            self.write_bcd(MINUTES_REG, 0x7F, minutes);
+            return;
+        }
+        self.write_bcd(MINUTES_REG, 0x7F, 0);
+
+        let hours = self.read_bcd(HOURS_REG, 0x3F) + 1;
+        if hours < 24 {
+            self.write_bcd(HOURS_REG, 0x3F, hours);
+            return;
+        }
+        self.write_bcd(HOURS_REG, 0x3F, 0);
+
+        self.tick_day();
+    }
+
+    fn tick_day(&mut self) {
+        let week_day = (self.read_bcd(WEEKDAY_REG, 0x07) + 1) % 7;
+        self.write_bcd(WEEKDAY_REG, 0x07, week_day);
+
+        let year = self.read_bcd(YEARS_REG, 0xFF);
+        let month = self.read_bcd(MONTHS_REG, 0x1F);
+        let days_in_month = match month {
+            4 | 6 | 9 | 11 => 30,
+            // The chip considers every year divisible by 4 a leap year.
+            2 if year.is_multiple_of(4) => 29,
+            2 => 28,
+            _ => 31,
+        };
+
+        let day = self.read_bcd(DAYS_REG, 0x3F) + 1;
+        if day <= days_in_month {
+            self.write_bcd(DAYS_REG, 0x3F, day);
+            return;
+        }
+        self.write_bcd(DAYS_REG, 0x3F, 1);
+
+        if month < 12 {
+            self.write_bcd(MONTHS_REG, 0x1F, month + 1);
+            return;
+        }
+        self.write_bcd(MONTHS_REG, 0x1F, 1);
+
+        if year < 99 {
+            self.write_bcd(YEARS_REG, 0xFF, year + 1);
+            return;
+        }
+        self.write_bcd(YEARS_REG, 0xFF, 0);
+        self.registers[MONTHS_REG as usize] ^= MONTHS_CENTURY;
+    }
+
+    /// Set AF if all enabled alarm fields match. Evaluated on each minute.
+    fn check_alarm(&mut self) {
+        let fields = [
+            (MINUTES_REG, 0x7F),
+            (HOURS_REG, 0x3F),
+            (DAYS_REG, 0x3F),
+            (WEEKDAY_REG, 0x07),
+        ];
+        let mut enabled = false;
+        let mut matching = true;
+
+        for (offset, (reg, mask)) in fields.into_iter().enumerate() {
+            let alarm = self.registers[MINUTE_ALARM_REG as usize + offset];
+            if alarm & 0x80 == 0 {
+                enabled = true;
+                matching &= alarm & mask == self.registers[reg as usize] & mask;
+            }
+        }
+
+        if enabled && matching {
+            self.registers[CONTROL_STATUS_2_REG as usize] |= CTRL2_AF;
+        }
+    }
+
+    fn tick_timer(&mut self) {
+        if self.timer_reload == 0 {
+            return;
+        }
+
+        let value = &mut self.registers[TIMER_REG as usize];
+        if *value <= 1 {
+            *value = self.timer_reload;
+            self.registers[CONTROL_STATUS_2_REG as usize] |= CTRL2_TF;
+        } else {
+            *value -= 1;
+        }
+    }
+}
+
+impl ErrorType for Bm8563Sim {
+    type Error = SimError;
+}
+
+impl I2c for Bm8563Sim {
+    fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        if address != self.address {
+            return Err(SimError::NoAcknowledge);
+        }
+
+        for operation in operations {
+            match operation {
+                Operation::Write(bytes) => {
+                    // First byte written sets the register address.
+                    if let Some((pointer, data)) = bytes.split_first() {
+                        self.pointer = pointer & 0x0F;
+                        for value in data {
+                            self.write_register(self.pointer, *value);
+                            self.pointer = (self.pointer + 1) & 0x0F;
+                        }
+                    }
+                }
+                Operation::Read(buffer) => {
+                    for value in buffer.iter_mut() {
+                        *value = self.registers[self.pointer as usize];
+                        self.pointer = (self.pointer + 1) & 0x0F;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
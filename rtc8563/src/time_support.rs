@@ -0,0 +1,72 @@
+//! Conversions from and to the `time` crate types
+//!
+//! Enabled with the `time` feature.
+use crate::{ConversionError, Date, DateTime, Time};
+
+impl TryFrom<Date> for time::Date {
+    type Error = ConversionError;
+
+    fn try_from(date: Date) -> Result<Self, Self::Error> {
+        let month = time::Month::try_from(date.month).map_err(|_| ConversionError)?;
+
+        time::Date::from_calendar_date(date.year as i32, month, date.day)
+            .map_err(|_| ConversionError)
+    }
+}
+
+impl TryFrom<time::Date> for Date {
+    type Error = ConversionError;
+
+    /// Fails if the year is out of the range supported by the chip.
+    fn try_from(date: time::Date) -> Result<Self, Self::Error> {
+        let date = Date {
+            week_day: date.weekday().number_days_from_sunday(),
+            month: date.month() as u8,
+            day: date.day(),
+            year: i16::try_from(date.year()).map_err(|_| ConversionError)?,
+        };
+
+        date.is_valid().then_some(date).ok_or(ConversionError)
+    }
+}
+
+impl TryFrom<Time> for time::Time {
+    type Error = ConversionError;
+
+    fn try_from(time: Time) -> Result<Self, Self::Error> {
+        time::Time::from_hms(time.hours, time.minutes, time.seconds).map_err(|_| ConversionError)
+    }
+}
+
+impl From<time::Time> for Time {
+    /// Sub-second precision is truncated.
+    fn from(time: time::Time) -> Self {
+        Time {
+            hours: time.hour(),
+            minutes: time.minute(),
+            seconds: time.second(),
+        }
+    }
+}
+
+impl TryFrom<DateTime> for time::PrimitiveDateTime {
+    type Error = ConversionError;
+
+    fn try_from(datetime: DateTime) -> Result<Self, Self::Error> {
+        Ok(time::PrimitiveDateTime::new(
+            datetime.date.try_into()?,
+            datetime.time.try_into()?,
+        ))
+    }
+}
+
+impl TryFrom<time::PrimitiveDateTime> for DateTime {
+    type Error = ConversionError;
+
+    fn try_from(datetime: time::PrimitiveDateTime) -> Result<Self, Self::Error> {
+        Ok(DateTime {
+            date: datetime.date().try_into()?,
+            time: datetime.time().into(),
+        })
+    }
+}
@@ -0,0 +1,265 @@
+//! Countdown timer support
+//!
+//! The BM8563 countdown timer decrements an 8 bit value at the rate of the
+//! selected source clock. When it reaches zero, the timer flag (TF) is raised,
+//! the value is reloaded and the countdown starts over.
+use core::time::Duration;
+
+use embedded_hal::i2c::I2c;
+
+use crate::{Error, Interrupts, Rtc8563, TIMER_CONTROL_REG, TIMER_REG};
+
+/// Timer enable bit (TE) of the timer control register.
+pub(crate) const TIMER_ENABLE: u8 = 0x80;
+
+/// Countdown timer source clock.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimerSource {
+    /// 4096 Hz, 244 us resolution, up to 62 ms.
+    Hz4096 = 0,
+    /// 64 Hz, 15.6 ms resolution, up to 3.98 s.
+    Hz64 = 1,
+    /// 1 Hz, 1 s resolution, up to 255 s.
+    Hz1 = 2,
+    /// 1/60 Hz, 1 min resolution, up to 255 min.
+    Hz1_60 = 3,
+}
+
+impl TimerSource {
+    /// All sources, from the finest to the coarsest resolution.
+    const ALL: [TimerSource; 4] = [
+        TimerSource::Hz4096,
+        TimerSource::Hz64,
+        TimerSource::Hz1,
+        TimerSource::Hz1_60,
+    ];
+
+    /// Source period as a fraction of a second (numerator, denominator).
+    fn period(self) -> (u64, u64) {
+        match self {
+            TimerSource::Hz4096 => (1, 4096),
+            TimerSource::Hz64 => (1, 64),
+            TimerSource::Hz1 => (1, 1),
+            TimerSource::Hz1_60 => (60, 1),
+        }
+    }
+
+    pub(crate) fn from_bits(bits: u8) -> Self {
+        Self::ALL[(bits & 0x03) as usize]
+    }
+}
+
+/// Error returned when a duration can't be represented by the timer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimerError {
+    /// Duration is shorter than one period of the fastest source.
+    TooShort,
+    /// Duration is longer than 255 minutes.
+    TooLong,
+}
+
+/// Countdown timer setting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimerConfig {
+    /// Clock decrementing the timer.
+    pub source: TimerSource,
+    /// Number of source periods between two timer events (1-255).
+    pub count: u8,
+}
+
+impl TimerConfig {
+    /// Build the timer setting closest to the given duration.
+    ///
+    /// Each source is tried with the count rounded to the nearest period, and
+    /// the setting with the smallest error is selected. On a tie, the source
+    /// with the finest resolution wins.
+    pub fn from_duration(duration: Duration) -> Result<Self, TimerError> {
+        let nanos = duration.as_nanos();
+
+        // Errors are compared in units of 1/4096 ns, a multiple of all the
+        // source periods.
+        let mut best: Option<(u128, TimerConfig)> = None;
+        for source in TimerSource::ALL {
+            let (num, den) = source.period();
+            let period_nanos = num as u128 * 1_000_000_000;
+            let count = (nanos * den as u128 + period_nanos / 2) / period_nanos;
+
+            if count == 0 && source == TimerSource::Hz4096 {
+                return Err(TimerError::TooShort);
+            }
+            if count > u8::MAX as u128 && source == TimerSource::Hz1_60 {
+                return Err(TimerError::TooLong);
+            }
+
+            let count = count.clamp(1, u8::MAX as u128);
+            let scale = 4096 / den as u128;
+            let error = (count * period_nanos * scale).abs_diff(nanos * 4096);
+            if best.is_none_or(|(best_error, _)| error < best_error) {
+                best = Some((
+                    error,
+                    TimerConfig {
+                        source,
+                        count: count as u8,
+                    },
+                ));
+            }
+        }
+
+        best.map(|(_, config)| config).ok_or(TimerError::TooLong)
+    }
+
+    /// Encode a timer write transfer, with the timer stopped.
+    pub(crate) fn write_buffer(&self) -> [u8; 3] {
+        [TIMER_CONTROL_REG, self.source as u8, self.count]
+    }
+
+    /// Time between two timer events.
+    pub fn duration(&self) -> Duration {
+        let (num, den) = self.source.period();
+        let nanos = self.count as u64 * num * 1_000_000_000 / den;
+
+        Duration::from_nanos(nanos)
+    }
+}
+
+impl Rtc8563 {
+    /// Configure the countdown timer.
+    ///
+    /// The timer is stopped, use `start_timer()` to start counting.
+    pub fn set_timer<I2C: I2c>(
+        &self,
+        bus: &mut I2C,
+        config: &TimerConfig,
+    ) -> Result<(), Error<I2C::Error>> {
+        let buffer = config.write_buffer();
+
+        bus.write(self.address, &buffer)?;
+
+        Ok(())
+    }
+
+    /// Get the source clock of the countdown timer.
+    pub fn get_timer_source<I2C: I2c>(
+        &self,
+        bus: &mut I2C,
+    ) -> Result<TimerSource, Error<I2C::Error>> {
+        let control = self.read_register(bus, TIMER_CONTROL_REG)?;
+
+        Ok(TimerSource::from_bits(control))
+    }
+
+    /// Start the countdown timer.
+    pub fn start_timer<I2C: I2c>(&self, bus: &mut I2C) -> Result<(), Error<I2C::Error>> {
+        let control = self.read_register(bus, TIMER_CONTROL_REG)?;
+
+        self.write_register(bus, TIMER_CONTROL_REG, control | TIMER_ENABLE)
+    }
+
+    /// Stop the countdown timer.
+    pub fn stop_timer<I2C: I2c>(&self, bus: &mut I2C) -> Result<(), Error<I2C::Error>> {
+        let control = self.read_register(bus, TIMER_CONTROL_REG)?;
+
+        self.write_register(bus, TIMER_CONTROL_REG, control & !TIMER_ENABLE)
+    }
+
+    /// Query if the countdown timer is running.
+    pub fn is_timer_running<I2C: I2c>(&self, bus: &mut I2C) -> Result<bool, Error<I2C::Error>> {
+        let control = self.read_register(bus, TIMER_CONTROL_REG)?;
+
+        Ok(control & TIMER_ENABLE == TIMER_ENABLE)
+    }
+
+    /// Read the remaining count before the next timer event.
+    pub fn get_timer_value<I2C: I2c>(&self, bus: &mut I2C) -> Result<u8, Error<I2C::Error>> {
+        self.read_register(bus, TIMER_REG)
+    }
+
+    /// Query if the timer has fired since it was last acknowledged.
+    pub fn timer_fired<I2C: I2c>(&self, bus: &mut I2C) -> Result<bool, Error<I2C::Error>> {
+        Ok(self.get_flags(bus)?.timer)
+    }
+
+    /// Clear the timer flag (TF).
+    pub fn acknowledge_timer<I2C: I2c>(&self, bus: &mut I2C) -> Result<(), Error<I2C::Error>> {
+        self.clear_flags(bus, Interrupts::TIMER)
+    }
+
+    /// Enable or disable the timer interrupt (TIE).
+    ///
+    /// When enabled, the INT pin is asserted while the timer flag is set.
+    pub fn set_timer_interrupt<I2C: I2c>(
+        &self,
+        bus: &mut I2C,
+        enable: bool,
+    ) -> Result<(), Error<I2C::Error>> {
+        if enable {
+            self.enable_interrupts(bus, Interrupts::TIMER)
+        } else {
+            self.disable_interrupts(bus, Interrupts::TIMER)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(source: TimerSource, count: u8) -> Result<TimerConfig, TimerError> {
+        Ok(TimerConfig { source, count })
+    }
+
+    #[test]
+    fn finest_source() {
+        assert_eq!(
+            TimerConfig::from_duration(Duration::from_millis(500)),
+            config(TimerSource::Hz64, 32)
+        );
+        assert_eq!(
+            TimerConfig::from_duration(Duration::from_secs(10)),
+            config(TimerSource::Hz1, 10)
+        );
+        assert_eq!(
+            TimerConfig::from_duration(Duration::from_secs(5 * 60)),
+            config(TimerSource::Hz1_60, 5)
+        );
+    }
+
+    #[test]
+    fn closest_duration() {
+        // Just above the 1 Hz range: 255 s is closer than 4 min.
+        assert_eq!(
+            TimerConfig::from_duration(Duration::from_millis(255_500)),
+            config(TimerSource::Hz1, 255)
+        );
+        assert_eq!(
+            TimerConfig::from_duration(Duration::from_secs(256)),
+            config(TimerSource::Hz1, 255)
+        );
+        // 4 min 50 s: 5 min is closer than 255 s.
+        assert_eq!(
+            TimerConfig::from_duration(Duration::from_secs(4 * 60 + 50)),
+            config(TimerSource::Hz1_60, 5)
+        );
+        // Just above the 64 Hz range: 255/64 s is closer than 4 s.
+        assert_eq!(
+            TimerConfig::from_duration(Duration::from_millis(3990)),
+            config(TimerSource::Hz64, 255)
+        );
+    }
+
+    #[test]
+    fn out_of_range() {
+        assert_eq!(
+            TimerConfig::from_duration(Duration::from_micros(100)),
+            Err(TimerError::TooShort)
+        );
+        assert_eq!(
+            TimerConfig::from_duration(Duration::from_secs(256 * 60)),
+            Err(TimerError::TooLong)
+        );
+        assert_eq!(
+            TimerConfig::from_duration(Duration::from_secs(255 * 60)),
+            config(TimerSource::Hz1_60, 255)
+        );
+    }
+}
@@ -0,0 +1,620 @@
+//! Time zones and daylight saving time
+//!
+//! The chip has no notion of time zones, the recommended setup is to keep it
+//! in UTC and convert to local time for display. `TimeZone` is parsed from a
+//! POSIX TZ string, as found in the last line of the tzdata files, e.g.
+//! `CET-1CEST,M3.5.0,M10.5.0/3` for Central Europe:
+//!
+//! ```text
+//! std offset [dst [offset] [,start[/time],end[/time]]]
+//! ```
+//!
+//!  - `std` and `dst` are the zone abbreviations, either 3 or more letters or
+//!    quoted within `<>` (e.g. `<+03>`).
+//!  - `offset` is `[+|-]hh[:mm[:ss]]`, the time to add to the local time to
+//!    get UTC: positive west of Greenwich. DST defaults to one hour ahead of
+//!    standard time.
+//!  - `start` and `end` are the DST transition days: `Jn` (1-365, 29 February
+//!    never counted), `n` (0-365, 29 February counted) or `Mm.w.d` (day `d`,
+//!    0 = Sunday, of week `w` of month `m`, week 5 being the last one).
+//!  - `time` is the local time of the transition, default 02:00:00. It can
+//!    be negative or above 24 hours (RFC 8536 extension).
+//!
+//! When the rules are omitted, the US rules (`M3.2.0,M11.1.0`) are used.
+use core::ops::RangeInclusive;
+
+use crate::calendar::{days_in_month, is_leap_year};
+use crate::unix::{civil_from_days, days_from_civil, weekday_from_days};
+use crate::{Alarm, DateTime};
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// Default DST transition time (02:00:00).
+const DEFAULT_TRANSITION_TIME: i32 = 2 * 3600;
+
+/// Error returned when parsing a TZ string fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TzParseError {
+    /// Zone abbreviation missing, too short or not terminated.
+    InvalidName,
+    /// UTC offset or transition time missing or out of range.
+    InvalidOffset,
+    /// DST transition day missing or out of range.
+    InvalidRule,
+    /// Unexpected characters after the end of the TZ string.
+    TrailingCharacters,
+}
+
+/// Day of the year of a DST transition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TransitionDay {
+    /// `Jn`: day 1-365, 29 February is never counted.
+    Julian1(u16),
+    /// `n`: day 0-365, 29 February is counted in leap years.
+    Julian0(u16),
+    /// `Mm.w.d`: weekday `d` of week `w` (1-5, 5 = last) of month `m`.
+    MonthWeekDay { month: u8, week: u8, week_day: u8 },
+}
+
+impl TransitionDay {
+    /// Zero based day of the year.
+    fn day_of_year(self, year: i16) -> i64 {
+        match self {
+            TransitionDay::Julian1(day) => {
+                let day = day as i64 - 1;
+                if is_leap_year(year) && day >= 59 {
+                    day + 1
+                } else {
+                    day
+                }
+            }
+            TransitionDay::Julian0(day) => day as i64,
+            TransitionDay::MonthWeekDay {
+                month,
+                week,
+                week_day,
+            } => {
+                let first = days_from_civil(year as i64, month, 1);
+                let first_week_day = weekday_from_days(first);
+                let mut day = 1 + (week_day + 7 - first_week_day) % 7 + (week - 1) * 7;
+                while day > days_in_month(year, month) {
+                    day -= 7;
+                }
+
+                days_from_civil(year as i64, month, day) - days_from_civil(year as i64, 1, 1)
+            }
+        }
+    }
+}
+
+/// DST transition rule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Transition {
+    day: TransitionDay,
+    /// Local time of the transition, in seconds.
+    time: i32,
+}
+
+impl Transition {
+    /// Unix timestamp of the transition, given the UTC offset in effect
+    /// before it.
+    fn timestamp(&self, year: i16, offset: i32) -> i64 {
+        (days_from_civil(year as i64, 1, 1) + self.day.day_of_year(year)) * SECONDS_PER_DAY
+            + self.time as i64
+            - offset as i64
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Dst<'a> {
+    name: &'a str,
+    offset: i32,
+    start: Transition,
+    end: Transition,
+}
+
+/// UTC offset in effect at a given time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UtcOffset<'a> {
+    /// Zone abbreviation, e.g. `CEST`.
+    pub name: &'a str,
+    /// Seconds to add to UTC to get the local time (positive east of
+    /// Greenwich).
+    pub seconds: i32,
+    /// Query if daylight saving time is in effect.
+    pub is_dst: bool,
+}
+
+/// Time zone with optional daylight saving time rules.
+///
+/// The zone abbreviations borrow from the parsed string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeZone<'a> {
+    name: &'a str,
+    offset: i32,
+    dst: Option<Dst<'a>>,
+}
+
+impl TimeZone<'static> {
+    /// Coordinated Universal Time.
+    pub const UTC: TimeZone<'static> = TimeZone {
+        name: "UTC",
+        offset: 0,
+        dst: None,
+    };
+}
+
+impl<'a> TimeZone<'a> {
+    /// Parse a POSIX TZ string.
+    pub fn parse(tz: &'a str) -> Result<Self, TzParseError> {
+        let mut parser = Parser { input: tz, pos: 0 };
+
+        let name = parser.name()?;
+        let offset = -parser.offset(24)?;
+
+        let dst = if parser.at_end() {
+            None
+        } else {
+            let dst_name = parser.name()?;
+            let dst_offset = match parser.peek() {
+                None | Some(b',') => offset + 3600,
+                _ => -parser.offset(24)?,
+            };
+            let (start, end) = if parser.at_end() {
+                (
+                    Transition {
+                        day: TransitionDay::MonthWeekDay {
+                            month: 3,
+                            week: 2,
+                            week_day: 0,
+                        },
+                        time: DEFAULT_TRANSITION_TIME,
+                    },
+                    Transition {
+                        day: TransitionDay::MonthWeekDay {
+                            month: 11,
+                            week: 1,
+                            week_day: 0,
+                        },
+                        time: DEFAULT_TRANSITION_TIME,
+                    },
+                )
+            } else {
+                parser.expect(b',', TzParseError::InvalidRule)?;
+                let start = parser.transition()?;
+                parser.expect(b',', TzParseError::InvalidRule)?;
+                (start, parser.transition()?)
+            };
+
+            Some(Dst {
+                name: dst_name,
+                offset: dst_offset,
+                start,
+                end,
+            })
+        };
+
+        if !parser.at_end() {
+            return Err(TzParseError::TrailingCharacters);
+        }
+
+        Ok(TimeZone { name, offset, dst })
+    }
+
+    /// Standard time UTC offset.
+    pub fn standard_offset(&self) -> UtcOffset<'a> {
+        UtcOffset {
+            name: self.name,
+            seconds: self.offset,
+            is_dst: false,
+        }
+    }
+
+    /// Daylight saving time UTC offset, if the zone observes DST.
+    pub fn dst_offset(&self) -> Option<UtcOffset<'a>> {
+        self.dst.map(|dst| UtcOffset {
+            name: dst.name,
+            seconds: dst.offset,
+            is_dst: true,
+        })
+    }
+
+    /// UTC offset in effect at a Unix timestamp.
+    pub fn offset_at(&self, timestamp: i64) -> UtcOffset<'a> {
+        let Some(dst) = self.dst else {
+            return self.standard_offset();
+        };
+
+        let days = (timestamp + self.offset as i64).div_euclid(SECONDS_PER_DAY);
+        let (year, _, _) = civil_from_days(days);
+        let year = year.clamp(i16::MIN as i64, i16::MAX as i64) as i16;
+
+        let start = dst.start.timestamp(year, self.offset);
+        let end = dst.end.timestamp(year, dst.offset);
+        let is_dst = if start < end {
+            (start..end).contains(&timestamp)
+        } else {
+            // Southern hemisphere: DST spans the new year.
+            !(end..start).contains(&timestamp)
+        };
+
+        if is_dst {
+            self.dst_offset().unwrap_or(self.standard_offset())
+        } else {
+            self.standard_offset()
+        }
+    }
+
+    /// Convert a UTC date and time to local time.
+    ///
+    /// Return `None` if the result falls outside the range supported by the
+    /// chip.
+    pub fn to_local(&self, utc: &DateTime) -> Option<DateTime> {
+        let timestamp = utc.to_unix_timestamp();
+
+        DateTime::from_unix_timestamp(timestamp + self.offset_at(timestamp).seconds as i64)
+    }
+
+    /// Convert a local date and time to UTC.
+    ///
+    /// A local time repeated when DST ends resolves to its first occurrence.
+    /// A local time skipped when DST starts is interpreted with the offset
+    /// in effect before the transition, so 02:30 in a skipped 02:00-03:00
+    /// hour gives the same instant as 03:30.
+    ///
+    /// Return `None` if the result falls outside the range supported by the
+    /// chip.
+    pub fn to_utc(&self, local: &DateTime) -> Option<DateTime> {
+        DateTime::from_unix_timestamp(self.local_to_timestamp(local.to_unix_timestamp()))
+    }
+
+    /// Alarm firing at a local date and time.
+    ///
+    /// The chip compares the alarm against its UTC clock, so the day, hours
+    /// and minutes are converted to UTC. A repeating local alarm has to be
+    /// computed again when it fires, as the UTC offset changes with DST.
+    pub fn alarm_at(&self, local: &DateTime) -> Option<Alarm> {
+        let utc = self.to_utc(local)?;
+
+        Some(Alarm {
+            minutes: Some(utc.time.minutes),
+            hours: Some(utc.time.hours),
+            day: Some(utc.date.day),
+            week_day: None,
+        })
+    }
+
+    fn local_to_timestamp(&self, local: i64) -> i64 {
+        let Some(dst) = self.dst else {
+            return local - self.offset as i64;
+        };
+
+        let standard = local - self.offset as i64;
+        let daylight = local - dst.offset as i64;
+        let standard_valid = !self.offset_at(standard).is_dst;
+        let daylight_valid = self.offset_at(daylight).is_dst;
+
+        match (standard_valid, daylight_valid) {
+            (true, true) => standard.min(daylight),
+            (true, false) => standard,
+            (false, true) => daylight,
+            // Skipped local time: use the smaller offset, in effect before
+            // the clocks moved forward.
+            (false, false) => standard.max(daylight),
+        }
+    }
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<u8> {
+        self.input.as_bytes().get(self.pos).copied()
+    }
+
+    fn at_end(&self) -> bool {
+        self.pos == self.input.len()
+    }
+
+    fn expect(&mut self, byte: u8, error: TzParseError) -> Result<(), TzParseError> {
+        if self.peek() != Some(byte) {
+            return Err(error);
+        }
+        self.pos += 1;
+
+        Ok(())
+    }
+
+    fn take_while(&mut self, predicate: impl Fn(u8) -> bool) -> &'a str {
+        let start = self.pos;
+        while self.peek().is_some_and(&predicate) {
+            self.pos += 1;
+        }
+
+        &self.input[start..self.pos]
+    }
+
+    fn name(&mut self) -> Result<&'a str, TzParseError> {
+        let name = if self.peek() == Some(b'<') {
+            self.pos += 1;
+            let name = self.take_while(|c| c.is_ascii_alphanumeric() || c == b'+' || c == b'-');
+            self.expect(b'>', TzParseError::InvalidName)?;
+            name
+        } else {
+            self.take_while(|c| c.is_ascii_alphabetic())
+        };
+
+        if name.len() < 3 {
+            return Err(TzParseError::InvalidName);
+        }
+
+        Ok(name)
+    }
+
+    fn number(&mut self, max: u32) -> Result<u32, TzParseError> {
+        let digits = self.take_while(|c| c.is_ascii_digit());
+        match digits.parse::<u32>() {
+            Ok(value) if value <= max => Ok(value),
+            _ => Err(TzParseError::InvalidOffset),
+        }
+    }
+
+    /// `[+|-]hh[:mm[:ss]]`, in seconds.
+    fn offset(&mut self, max_hours: u32) -> Result<i32, TzParseError> {
+        let sign = match self.peek() {
+            Some(b'-') => {
+                self.pos += 1;
+                -1
+            }
+            Some(b'+') => {
+                self.pos += 1;
+                1
+            }
+            _ => 1,
+        };
+
+        let mut seconds = self.number(max_hours)? * 3600;
+        if self.peek() == Some(b':') {
+            self.pos += 1;
+            seconds += self.number(59)? * 60;
+            if self.peek() == Some(b':') {
+                self.pos += 1;
+                seconds += self.number(59)?;
+            }
+        }
+
+        Ok(sign * seconds as i32)
+    }
+
+    fn rule_number(&mut self, range: RangeInclusive<u32>) -> Result<u32, TzParseError> {
+        let digits = self.take_while(|c| c.is_ascii_digit());
+        match digits.parse::<u32>() {
+            Ok(value) if range.contains(&value) => Ok(value),
+            _ => Err(TzParseError::InvalidRule),
+        }
+    }
+
+    fn transition(&mut self) -> Result<Transition, TzParseError> {
+        let day = match self.peek() {
+            Some(b'J') => {
+                self.pos += 1;
+                TransitionDay::Julian1(self.rule_number(1..=365)? as u16)
+            }
+            Some(b'M') => {
+                self.pos += 1;
+                let month = self.rule_number(1..=12)? as u8;
+                self.expect(b'.', TzParseError::InvalidRule)?;
+                let week = self.rule_number(1..=5)? as u8;
+                self.expect(b'.', TzParseError::InvalidRule)?;
+                let week_day = self.rule_number(0..=6)? as u8;
+                TransitionDay::MonthWeekDay {
+                    month,
+                    week,
+                    week_day,
+                }
+            }
+            _ => TransitionDay::Julian0(self.rule_number(0..=365)? as u16),
+        };
+
+        let time = if self.peek() == Some(b'/') {
+            self.pos += 1;
+            self.offset(167)?
+        } else {
+            DEFAULT_TRANSITION_TIME
+        };
+
+        Ok(Transition { day, time })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Date, Time};
+
+    fn datetime(year: i16, month: u8, day: u8, hours: u8, minutes: u8, seconds: u8) -> DateTime {
+        DateTime {
+            date: Date::new(year, month, day).unwrap(),
+            time: Time {
+                hours,
+                minutes,
+                seconds,
+            },
+        }
+    }
+
+    /// UTC offset in effect at a UTC date and time.
+    fn offset_at(
+        tz: &TimeZone,
+        year: i16,
+        month: u8,
+        day: u8,
+        hours: u8,
+        minutes: u8,
+        seconds: u8,
+    ) -> i32 {
+        tz.offset_at(datetime(year, month, day, hours, minutes, seconds).to_unix_timestamp())
+            .seconds
+    }
+
+    #[test]
+    fn northern_hemisphere() {
+        let tz = TimeZone::parse("EST5EDT").unwrap();
+        assert_eq!(
+            tz.standard_offset(),
+            UtcOffset {
+                name: "EST",
+                seconds: -5 * 3600,
+                is_dst: false
+            }
+        );
+        assert_eq!(
+            tz.dst_offset(),
+            Some(UtcOffset {
+                name: "EDT",
+                seconds: -4 * 3600,
+                is_dst: true
+            })
+        );
+
+        // US rules: 2024-03-10 02:00 EST and 2024-11-03 02:00 EDT
+        assert_eq!(offset_at(&tz, 2024, 3, 10, 6, 59, 59), -5 * 3600);
+        assert_eq!(offset_at(&tz, 2024, 3, 10, 7, 0, 0), -4 * 3600);
+        assert_eq!(offset_at(&tz, 2024, 11, 3, 5, 59, 59), -4 * 3600);
+        assert_eq!(offset_at(&tz, 2024, 11, 3, 6, 0, 0), -5 * 3600);
+    }
+
+    #[test]
+    fn southern_hemisphere() {
+        let tz = TimeZone::parse("AEST-10AEDT,M10.1.0,M4.1.0/3").unwrap();
+
+        assert_eq!(offset_at(&tz, 2024, 1, 15, 0, 0, 0), 11 * 3600);
+        // 2024-04-07 03:00 AEDT
+        assert_eq!(offset_at(&tz, 2024, 4, 6, 15, 59, 59), 11 * 3600);
+        assert_eq!(offset_at(&tz, 2024, 4, 6, 16, 0, 0), 10 * 3600);
+        assert_eq!(offset_at(&tz, 2024, 7, 1, 0, 0, 0), 10 * 3600);
+        // 2024-10-06 02:00 AEST
+        assert_eq!(offset_at(&tz, 2024, 10, 5, 15, 59, 59), 10 * 3600);
+        assert_eq!(offset_at(&tz, 2024, 10, 5, 16, 0, 0), 11 * 3600);
+        assert_eq!(offset_at(&tz, 2024, 12, 31, 23, 59, 59), 11 * 3600);
+    }
+
+    #[test]
+    fn quoted_names() {
+        let tz = TimeZone::parse("<+03>-3").unwrap();
+        assert_eq!(
+            tz.offset_at(0),
+            UtcOffset {
+                name: "+03",
+                seconds: 3 * 3600,
+                is_dst: false
+            }
+        );
+        assert_eq!(tz.dst_offset(), None);
+
+        // Negative transition time: 2024-03-30 22:00 local
+        let tz = TimeZone::parse("<-02>2<-01>,M3.5.0/-1,M10.5.0/0").unwrap();
+        let timestamp = datetime(2024, 3, 31, 1, 0, 0).to_unix_timestamp();
+        assert_eq!(tz.offset_at(timestamp - 1).name, "-02");
+        assert_eq!(tz.offset_at(timestamp).name, "-01");
+    }
+
+    #[test]
+    fn julian_days() {
+        // Day 60 is always 1 March
+        let tz = TimeZone::parse("AAA0BBB,J60,J300").unwrap();
+        assert_eq!(offset_at(&tz, 2023, 3, 1, 1, 59, 59), 0);
+        assert_eq!(offset_at(&tz, 2023, 3, 1, 2, 0, 0), 3600);
+        assert_eq!(offset_at(&tz, 2024, 3, 1, 1, 59, 59), 0);
+        assert_eq!(offset_at(&tz, 2024, 3, 1, 2, 0, 0), 3600);
+
+        // Zero based day 59 is 29 February in leap years
+        let tz = TimeZone::parse("AAA0BBB,59,300").unwrap();
+        assert_eq!(offset_at(&tz, 2023, 3, 1, 1, 59, 59), 0);
+        assert_eq!(offset_at(&tz, 2023, 3, 1, 2, 0, 0), 3600);
+        assert_eq!(offset_at(&tz, 2024, 2, 29, 1, 59, 59), 0);
+        assert_eq!(offset_at(&tz, 2024, 2, 29, 2, 0, 0), 3600);
+    }
+
+    #[test]
+    fn month_week_day() {
+        // Last Sunday of March and October, 2024-03-31 and 2024-10-27
+        let tz = TimeZone::parse("CET-1CEST,M3.5.0,M10.5.0/3").unwrap();
+        assert_eq!(offset_at(&tz, 2024, 3, 31, 0, 59, 59), 3600);
+        assert_eq!(offset_at(&tz, 2024, 3, 31, 1, 0, 0), 2 * 3600);
+        assert_eq!(offset_at(&tz, 2024, 10, 27, 0, 59, 59), 2 * 3600);
+        assert_eq!(offset_at(&tz, 2024, 10, 27, 1, 0, 0), 3600);
+
+        // Week 5 of a month with 4 Sundays: 2026-02-22
+        let tz = TimeZone::parse("AAA0BBB,M2.5.0,M10.1.0").unwrap();
+        assert_eq!(offset_at(&tz, 2026, 2, 22, 1, 59, 59), 0);
+        assert_eq!(offset_at(&tz, 2026, 2, 22, 2, 0, 0), 3600);
+    }
+
+    #[test]
+    fn explicit_time() {
+        let tz = TimeZone::parse("AAA0BBB-2,J1/1:30:15,J365/26").unwrap();
+        assert_eq!(offset_at(&tz, 2023, 1, 1, 1, 30, 14), 0);
+        assert_eq!(offset_at(&tz, 2023, 1, 1, 1, 30, 15), 2 * 3600);
+        // 31 December 26:00 local DST is 1 January 00:00 UTC
+        assert_eq!(offset_at(&tz, 2023, 12, 31, 23, 59, 59), 2 * 3600);
+        assert_eq!(offset_at(&tz, 2024, 1, 1, 0, 0, 0), 0);
+    }
+
+    #[test]
+    fn malformed() {
+        for (tz, error) in [
+            ("", TzParseError::InvalidName),
+            ("CE-1", TzParseError::InvalidName),
+            ("<+03-3", TzParseError::InvalidName),
+            ("CET", TzParseError::InvalidOffset),
+            ("CET25", TzParseError::InvalidOffset),
+            ("CET-1:60", TzParseError::InvalidOffset),
+            ("CET-1CEST,M13.5.0,M10.5.0", TzParseError::InvalidRule),
+            ("CET-1CEST,M3.6.0,M10.5.0", TzParseError::InvalidRule),
+            ("CET-1CEST,M3.5.0", TzParseError::InvalidRule),
+            ("CET-1CEST,J0,J300", TzParseError::InvalidRule),
+            ("CET-1CEST,M3.5.0,M10.5.0/168", TzParseError::InvalidOffset),
+            (
+                "CET-1CEST,M3.5.0,M10.5.0/3x",
+                TzParseError::TrailingCharacters,
+            ),
+        ] {
+            assert_eq!(TimeZone::parse(tz), Err(error), "{tz}");
+        }
+    }
+
+    #[test]
+    fn local_time() {
+        let tz = TimeZone::parse("CET-1CEST,M3.5.0,M10.5.0/3").unwrap();
+
+        let utc = datetime(2024, 3, 31, 0, 59, 59);
+        assert_eq!(tz.to_local(&utc), Some(datetime(2024, 3, 31, 1, 59, 59)));
+        let utc = datetime(2024, 3, 31, 1, 0, 0);
+        let local = datetime(2024, 3, 31, 3, 0, 0);
+        assert_eq!(tz.to_local(&utc), Some(local));
+        assert_eq!(tz.to_utc(&local), Some(utc));
+    }
+
+    #[test]
+    fn ambiguous_hour() {
+        let tz = TimeZone::parse("CET-1CEST,M3.5.0,M10.5.0/3").unwrap();
+
+        // 02:30 occurs at 00:30 UTC (CEST) then at 01:30 UTC (CET)
+        let local = datetime(2024, 10, 27, 2, 30, 0);
+        assert_eq!(tz.to_utc(&local), Some(datetime(2024, 10, 27, 0, 30, 0)));
+        assert_eq!(tz.to_local(&datetime(2024, 10, 27, 1, 30, 0)), Some(local));
+    }
+
+    #[test]
+    fn skipped_hour() {
+        let tz = TimeZone::parse("CET-1CEST,M3.5.0,M10.5.0/3").unwrap();
+
+        let utc = datetime(2024, 3, 31, 1, 30, 0);
+        assert_eq!(tz.to_utc(&datetime(2024, 3, 31, 2, 30, 0)), Some(utc));
+        assert_eq!(tz.to_utc(&datetime(2024, 3, 31, 3, 30, 0)), Some(utc));
+    }
+}
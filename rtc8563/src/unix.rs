@@ -0,0 +1,117 @@
+//! Unix timestamp conversions
+//!
+//! Conversions between `DateTime` and the number of seconds since
+//! 1970-01-01 00:00:00 UTC, ignoring leap seconds. Dates before 1970 give
+//! negative timestamps.
+use embedded_hal::i2c::I2c;
+
+use crate::{Date, DateTime, Error, Rtc8563, Time};
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// Number of days since 1970-01-01 of a date of the proleptic Gregorian
+/// calendar.
+///
+/// From Howard Hinnant's `days_from_civil()` algorithm.
+pub(crate) fn days_from_civil(year: i64, month: u8, day: u8) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let month_from_march = (month as i64 + 9) % 12;
+    let day_of_year = (153 * month_from_march + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Date (year, month, day) from the number of days since 1970-01-01.
+///
+/// From Howard Hinnant's `civil_from_days()` algorithm.
+pub(crate) fn civil_from_days(days: i64) -> (i64, u8, u8) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_from_march = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_from_march + 2) / 5 + 1) as u8;
+    let month = if month_from_march < 10 {
+        month_from_march + 3
+    } else {
+        month_from_march - 9
+    } as u8;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    (year, month, day)
+}
+
+/// Weekday (0 = Sunday) from the number of days since 1970-01-01.
+pub(crate) fn weekday_from_days(days: i64) -> u8 {
+    // 1970-01-01 was a Thursday
+    (days + 4).rem_euclid(7) as u8
+}
+
+impl DateTime {
+    /// Convert to seconds since the Unix epoch.
+    ///
+    /// The date and time must be valid. The weekday is ignored.
+    pub fn to_unix_timestamp(&self) -> i64 {
+        let days = days_from_civil(self.date.year as i64, self.date.month, self.date.day);
+
+        days * SECONDS_PER_DAY
+            + self.time.hours as i64 * 3600
+            + self.time.minutes as i64 * 60
+            + self.time.seconds as i64
+    }
+
+    /// Build from seconds since the Unix epoch.
+    ///
+    /// The weekday is computed. Return `None` if the date falls outside the
+    /// range supported by the chip (1900-2199).
+    pub fn from_unix_timestamp(timestamp: i64) -> Option<Self> {
+        let days = timestamp.div_euclid(SECONDS_PER_DAY);
+        let seconds_of_day = timestamp.rem_euclid(SECONDS_PER_DAY);
+        let (year, month, day) = civil_from_days(days);
+
+        let datetime = DateTime {
+            date: Date {
+                week_day: weekday_from_days(days),
+                month,
+                day,
+                year: i16::try_from(year).ok()?,
+            },
+            time: Time {
+                hours: (seconds_of_day / 3600) as u8,
+                minutes: (seconds_of_day % 3600 / 60) as u8,
+                seconds: (seconds_of_day % 60) as u8,
+            },
+        };
+
+        datetime.date.is_valid().then_some(datetime)
+    }
+}
+
+impl Rtc8563 {
+    /// Get the date and time as seconds since the Unix epoch.
+    ///
+    /// The clock integrity is not checked.
+    pub fn get_unix_time<I2C: I2c>(&self, bus: &mut I2C) -> Result<i64, Error<I2C::Error>> {
+        Ok(self.get_datetime(bus)?.to_unix_timestamp())
+    }
+
+    /// Set the date and time from seconds since the Unix epoch.
+    ///
+    /// Return `Error::InvalidDate` if the timestamp falls outside the range
+    /// supported by the chip (1900-2199), or `Error::YearOutOfRange` if the
+    /// year is out of the century policy range.
+    pub fn set_unix_time<I2C: I2c>(
+        &self,
+        bus: &mut I2C,
+        timestamp: i64,
+    ) -> Result<(), Error<I2C::Error>> {
+        let datetime = DateTime::from_unix_timestamp(timestamp).ok_or(Error::InvalidDate)?;
+
+        self.set_datetime(bus, &datetime)
+    }
+}
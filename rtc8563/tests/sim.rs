@@ -0,0 +1,197 @@
+//! Driver tests against the simulated chip.
+#![cfg(feature = "sim")]
+
+use core::time::Duration;
+
+use rtc8563::sim::Bm8563Sim;
+use rtc8563::*;
+
+fn datetime(year: i16, month: u8, day: u8, hours: u8, minutes: u8, seconds: u8) -> DateTime {
+    DateTime {
+        date: Date::new(year, month, day).unwrap(),
+        time: Time {
+            hours,
+            minutes,
+            seconds,
+        },
+    }
+}
+
+#[test]
+fn voltage_low_flag() {
+    let mut bus = Bm8563Sim::default();
+    let rtc = Rtc8563::new(RTC8563_DEFAULT_I2C_ADDRESS);
+
+    // Set on power-up, kept by init()
+    rtc.init(&mut bus).unwrap();
+    assert_eq!(rtc.clock_integrity(&mut bus).unwrap(), ClockIntegrity::Lost);
+    assert_eq!(rtc.get_valid_datetime(&mut bus).unwrap(), None);
+
+    // Cleared by setting the time
+    let now = datetime(2024, 3, 31, 14, 5, 9);
+    rtc.set_datetime(&mut bus, &now).unwrap();
+    assert_eq!(
+        rtc.clock_integrity(&mut bus).unwrap(),
+        ClockIntegrity::Guaranteed
+    );
+    assert_eq!(rtc.get_valid_datetime(&mut bus).unwrap(), Some(now));
+
+    bus.power_loss();
+    assert_eq!(rtc.clock_integrity(&mut bus).unwrap(), ClockIntegrity::Lost);
+    assert_eq!(rtc.get_datetime(&mut bus).unwrap(), now);
+}
+
+#[test]
+fn century_rollover() {
+    let mut bus = Bm8563Sim::default();
+    let before = datetime(2099, 12, 31, 23, 59, 59);
+
+    // The century bit toggles: 1900 with the default policy...
+    let rtc = Rtc8563::new(RTC8563_DEFAULT_I2C_ADDRESS);
+    rtc.set_datetime(&mut bus, &before).unwrap();
+    bus.advance(Duration::from_secs(1));
+    assert_eq!(
+        rtc.get_datetime(&mut bus).unwrap(),
+        datetime(1900, 1, 1, 0, 0, 0)
+    );
+
+    // ...2100 when the century bit means 2100-2199
+    let rtc = rtc.with_century_policy(CenturyPolicy::Set2100);
+    rtc.set_datetime(&mut bus, &before).unwrap();
+    bus.advance(Duration::from_secs(1));
+    assert_eq!(
+        rtc.get_datetime(&mut bus).unwrap(),
+        datetime(2100, 1, 1, 0, 0, 0)
+    );
+    assert_eq!(
+        rtc.set_datetime(&mut bus, &datetime(1999, 1, 1, 0, 0, 0)),
+        Err(Error::YearOutOfRange)
+    );
+}
+
+#[test]
+fn leap_year_2100() {
+    let mut bus = Bm8563Sim::default();
+    let rtc = Rtc8563::new(RTC8563_DEFAULT_I2C_ADDRESS).with_century_policy(CenturyPolicy::Set2100);
+
+    // The chip counts 29 February in year 00
+    rtc.set_datetime(&mut bus, &datetime(2100, 2, 28, 23, 59, 59))
+        .unwrap();
+    bus.advance(Duration::from_secs(1));
+    assert_eq!(rtc.get_datetime(&mut bus), Err(Error::InvalidRegister));
+    bus.advance(Duration::from_secs(24 * 60 * 60));
+    assert_eq!(
+        rtc.get_datetime(&mut bus).unwrap(),
+        datetime(2100, 3, 1, 0, 0, 0)
+    );
+
+    assert_eq!(
+        rtc.set_unix_time(&mut bus, datetime(1999, 1, 1, 0, 0, 0).to_unix_timestamp()),
+        Err(Error::YearOutOfRange)
+    );
+}
+
+#[test]
+fn alarm_flag() {
+    let mut bus = Bm8563Sim::default();
+    let rtc = Rtc8563::new(RTC8563_DEFAULT_I2C_ADDRESS);
+
+    // 2023-11-14 22:13:20 UTC
+    rtc.set_unix_time(&mut bus, 1_700_000_000).unwrap();
+    rtc.set_alarm(
+        &mut bus,
+        &Alarm {
+            minutes: Some(15),
+            hours: Some(22),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    rtc.set_alarm_interrupt(&mut bus, true).unwrap();
+
+    bus.advance(Duration::from_secs(60));
+    assert!(!rtc.alarm_fired(&mut bus).unwrap());
+    bus.advance(Duration::from_secs(60));
+    assert!(rtc.alarm_fired(&mut bus).unwrap());
+    assert!(bus.interrupt());
+
+    // A CPU reset does not lose the wake-up reason
+    rtc.init(&mut bus).unwrap();
+    assert!(rtc.alarm_fired(&mut bus).unwrap());
+    assert!(bus.interrupt());
+
+    rtc.acknowledge_alarm(&mut bus).unwrap();
+    assert!(!rtc.alarm_fired(&mut bus).unwrap());
+    assert!(!bus.interrupt());
+    assert_eq!(rtc.get_unix_time(&mut bus).unwrap(), 1_700_000_120);
+}
+
+#[test]
+fn timer_flag() {
+    let mut bus = Bm8563Sim::default();
+    let rtc = Rtc8563::new(RTC8563_DEFAULT_I2C_ADDRESS);
+
+    let config = TimerConfig::from_duration(Duration::from_millis(500)).unwrap();
+    rtc.set_timer(&mut bus, &config).unwrap();
+    rtc.set_timer_interrupt(&mut bus, true).unwrap();
+    rtc.start_timer(&mut bus).unwrap();
+
+    bus.advance(Duration::from_millis(499));
+    assert!(!rtc.timer_fired(&mut bus).unwrap());
+    bus.advance(Duration::from_millis(1));
+    assert!(rtc.timer_fired(&mut bus).unwrap());
+    assert!(!rtc.alarm_fired(&mut bus).unwrap());
+    assert!(bus.interrupt());
+
+    rtc.acknowledge_timer(&mut bus).unwrap();
+    assert!(!rtc.timer_fired(&mut bus).unwrap());
+    assert!(!bus.interrupt());
+    // Reloaded with 500 ms at 64 Hz
+    assert_eq!(rtc.get_timer_value(&mut bus).unwrap(), 32);
+}
+
+#[test]
+fn divider_reset() {
+    let mut bus = Bm8563Sim::default();
+    let rtc = Rtc8563::new(RTC8563_DEFAULT_I2C_ADDRESS);
+    rtc.set_datetime(&mut bus, &datetime(2024, 1, 1, 0, 0, 0))
+        .unwrap();
+
+    // Writing control/status 1 while running keeps the divider
+    bus.advance(Duration::from_millis(600));
+    rtc.init(&mut bus).unwrap();
+    bus.advance(Duration::from_millis(600));
+    assert_eq!(rtc.get_time(&mut bus).unwrap().seconds, 1);
+
+    // Releasing STOP restarts the second
+    rtc.stop_clock(&mut bus).unwrap();
+    bus.advance(Duration::from_secs(5));
+    rtc.start_clock(&mut bus).unwrap();
+    bus.advance(Duration::from_millis(999));
+    assert_eq!(rtc.get_time(&mut bus).unwrap().seconds, 1);
+    bus.advance(Duration::from_millis(1));
+    assert_eq!(rtc.get_time(&mut bus).unwrap().seconds, 2);
+}
+
+#[test]
+fn local_time() {
+    let mut bus = Bm8563Sim::default();
+    let rtc = Rtc8563::new(RTC8563_DEFAULT_I2C_ADDRESS);
+    let tz = TimeZone::parse("CET-1CEST,M3.5.0,M10.5.0/3").unwrap();
+
+    // One second before the switch to summer time
+    let utc: DateTime = "2024-03-31T00:59:59Z".parse().unwrap();
+    rtc.set_datetime(&mut bus, &utc).unwrap();
+    let local = tz.to_local(&rtc.get_datetime(&mut bus).unwrap()).unwrap();
+    assert_eq!(local.to_string(), "2024-03-31T01:59:59");
+
+    bus.advance(Duration::from_secs(1));
+    let utc = rtc.get_datetime(&mut bus).unwrap();
+    assert_eq!(
+        utc.to_unix_timestamp(),
+        rtc.get_unix_time(&mut bus).unwrap()
+    );
+    let local = tz.to_local(&utc).unwrap();
+    assert_eq!(local.to_string(), "2024-03-31T03:00:00");
+    assert_eq!(tz.to_utc(&local), Some(utc));
+}
@@ -29,7 +29,11 @@ pub use esp_hal::{
 pub use gc9a01::{mode::BufferedGraphics, prelude::*, Gc9a01, SPIDisplayInterface};
 
 // Touch screen driver (local)
-pub use crate::ft3267::{Ft3267, TouchPoint};
+pub use crate::ft3267::{
+    Ft3265Gesture, Ft3267, Ft3267Config, Gesture, GestureConfig, GestureRecognizer, Pinch,
+    PinchRecognizer, PowerMode as TouchPowerMode, RimRotation, RimRotationConfig,
+    Rotation as TouchRotation, SwipeDirection, TouchEvent, TouchPoint, Touches,
+};
 pub use crate::rtc8563::{Rtc8563, RTC8563_DEFAULT_I2C_ADDRESS};
 
 // Rotary encoder
@@ -53,6 +57,23 @@ pub type M5DialDisplay = Gc9a01<
 
 pub type M5DialEncoder = Rotary<Input<'static>, Input<'static>, DefaultPhase>;
 
+/// Display rotation set by `get_screen!()`, and followed by `get_touch!()`.
+pub const DISPLAY_ROTATION: DisplayRotation = DisplayRotation::Rotate180;
+
+/// Touch rotation matching a display rotation, so that touch coordinates
+/// line up with embedded-graphics coordinates.
+///
+/// The touch panel native orientation is the one of the display rotated by
+/// 180 degrees.
+pub fn touch_rotation(rotation: DisplayRotation) -> TouchRotation {
+    match rotation {
+        DisplayRotation::Rotate0 => TouchRotation::Deg180,
+        DisplayRotation::Rotate90 => TouchRotation::Deg270,
+        DisplayRotation::Rotate180 => TouchRotation::Deg0,
+        DisplayRotation::Rotate270 => TouchRotation::Deg90,
+    }
+}
+
 /// Holds the board peripherals
 pub struct M5DialBsp {
     /// HOLD signal, must be set HIGH after startup to maintain power. Can be set LOW to power off.
@@ -100,12 +121,8 @@ macro_rules! get_screen {
         let display_iface = SPIDisplayInterface::new(display_dev, rs);
 
         // Create the display driver, taking ownership of the above
-        let mut display = Gc9a01::new(
-            display_iface,
-            DisplayResolution240x240,
-            DisplayRotation::Rotate180,
-        )
-        .into_buffered_graphics();
+        let mut display = Gc9a01::new(display_iface, DisplayResolution240x240, DISPLAY_ROTATION)
+            .into_buffered_graphics();
 
         // Reset the display
         let mut display_reset =
@@ -130,7 +147,7 @@ macro_rules! get_screen {
 #[macro_export]
 macro_rules! get_touch {
     ($tp_i2c:ident) => {{
-        let touch = Ft3267::new(0);
+        let touch = Ft3267::new(touch_rotation(DISPLAY_ROTATION));
         let _ = touch.init(&mut $tp_i2c);
         touch
     }};